@@ -0,0 +1,477 @@
+//! Where puzzle inputs come from: A per-user cache, the puzzle server, or this repository.
+
+use crate::Answer;
+use crate::ParseError;
+use crate::ledger::{Ledger, Verdict};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const DEFAULT_URL: &str = "https://adventofcode.com/2024";
+const USER_AGENT: &str = "github.com/schubart/AdventOfCode_2024_Rust";
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+    /// The URL and what went wrong.
+    Http(String, String),
+    /// Not cached, no session to fetch it with, and no bundled input.
+    Missing(u8),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            Self::Parse(path, error) => write!(f, "{}:{error}", path.display()),
+            Self::Http(url, error) => write!(f, "{url}: {error}"),
+            Self::Missing(day) => write!(f, "no input for day {day}, set AOC_SESSION to fetch it"),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// Sends requests to the puzzle server.
+pub trait Http {
+    /// The body of a successful response, or what went wrong.
+    fn get(&self, url: &str, session: &str) -> Result<String, String>;
+
+    /// Posts a form, like `level=1&answer=42`.
+    fn post(&self, url: &str, session: &str, form: &str) -> Result<String, String>;
+}
+
+/// Plain HTTP/1.1 over a `TcpStream`, so `http://` URLs only. Good for local servers and tests.
+pub struct PlainHttp;
+
+impl Http for PlainHttp {
+    fn get(&self, url: &str, session: &str) -> Result<String, String> {
+        Self::request(url, session, "GET", None)
+    }
+
+    fn post(&self, url: &str, session: &str, form: &str) -> Result<String, String> {
+        Self::request(url, session, "POST", Some(form))
+    }
+}
+
+impl PlainHttp {
+    fn request(
+        url: &str,
+        session: &str,
+        method: &str,
+        form: Option<&str>,
+    ) -> Result<String, String> {
+        let io = |error: io::Error| error.to_string();
+
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or("only http:// URLs are supported")?;
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{host}:80")
+        };
+
+        let mut stream = TcpStream::connect(address).map_err(io)?;
+        write!(
+            stream,
+            "{method} /{path} HTTP/1.1\r\n\
+             Host: {host}\r\n\
+             Cookie: session={session}\r\n\
+             User-Agent: {USER_AGENT}\r\n\
+             Connection: close\r\n"
+        )
+        .map_err(io)?;
+        match form {
+            Some(form) => write!(
+                stream,
+                "Content-Type: application/x-www-form-urlencoded\r\n\
+                 Content-Length: {}\r\n\r\n{form}",
+                form.len()
+            ),
+            None => write!(stream, "\r\n"),
+        }
+        .map_err(io)?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).map_err(io)?;
+
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or("malformed response")?;
+        let status = head.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(status.to_string());
+        }
+        if head.to_lowercase().contains("transfer-encoding: chunked") {
+            return Err("chunked responses are not supported".to_string());
+        }
+
+        Ok(body.to_string())
+    }
+}
+
+/// Runs `curl`, which brings TLS for the real puzzle server.
+pub struct CurlHttp;
+
+impl Http for CurlHttp {
+    fn get(&self, url: &str, session: &str) -> Result<String, String> {
+        Self::request(url, session, &[])
+    }
+
+    fn post(&self, url: &str, session: &str, form: &str) -> Result<String, String> {
+        Self::request(url, session, &["--data-raw", form])
+    }
+}
+
+impl CurlHttp {
+    fn request(url: &str, session: &str, args: &[&str]) -> Result<String, String> {
+        let curl = |error: io::Error| format!("curl: {error}");
+
+        // The cookie goes in on stdin, not on the command line where other users could see it.
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--header", "@-"])
+            .args(["--user-agent", USER_AGENT, url])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(curl)?;
+        writeln!(child.stdin.take().unwrap(), "Cookie: session={session}").map_err(curl)?;
+        let output = child.wait_with_output().map_err(curl)?;
+
+        if output.status.success() {
+            String::from_utf8(output.stdout).map_err(|error| error.to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// The puzzle server, and who to ask as.
+pub struct Remote {
+    /// Like `https://adventofcode.com/2024`.
+    pub url: String,
+    pub session: String,
+    pub http: Box<dyn Http>,
+}
+
+impl Remote {
+    /// Uses `PlainHttp` for `http://` URLs and `CurlHttp` otherwise.
+    pub fn new(url: impl Into<String>, session: impl Into<String>) -> Self {
+        let url = url.into();
+        let http: Box<dyn Http> = if url.starts_with("http://") {
+            Box::new(PlainHttp)
+        } else {
+            Box::new(CurlHttp)
+        };
+
+        Self {
+            url,
+            session: session.into(),
+            http,
+        }
+    }
+
+    fn input(&self, day: u8) -> Result<String, InputError> {
+        let url = format!("{}/day/{day}/input", self.url);
+        self.http
+            .get(&url, &self.session)
+            .map_err(|error| InputError::Http(url, error))
+    }
+
+    /// Submits an answer and reports what the server made of it.
+    pub fn answer(&self, day: u8, part: u8, answer: &Answer) -> Result<Verdict, InputError> {
+        let url = format!("{}/day/{day}/answer", self.url);
+        let form = format!("level={part}&answer={}", url_encode(&answer.to_string()));
+        self.http
+            .post(&url, &self.session, &form)
+            .map(|html| Verdict::parse(&html))
+            .map_err(|error| InputError::Http(url, error))
+    }
+}
+
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Finds each day's input: In the cache, else on the server (and then caches it),
+/// else in this repository.
+pub struct Inputs {
+    /// One directory per day, holding `input.txt` and `input.answers`.
+    pub cache: PathBuf,
+    /// Where to fetch inputs that are not cached yet, if anywhere.
+    pub remote: Option<Remote>,
+    /// Whether to fall back to the inputs committed to this repository.
+    pub bundled: bool,
+}
+
+impl Inputs {
+    /// Configured by `AOC_CACHE` (default `~/.cache/aoc-2024`), `AOC_URL` and
+    /// `AOC_SESSION` (default: the `session` file in the cache).
+    pub fn from_env() -> Self {
+        let cache = env::var_os("AOC_CACHE")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("XDG_CACHE_HOME").map(|dir| Path::new(&dir).join("aoc-2024")))
+            .or_else(|| env::var_os("HOME").map(|dir| Path::new(&dir).join(".cache/aoc-2024")))
+            .unwrap_or_else(|| PathBuf::from(".aoc-2024"));
+        let session = env::var("AOC_SESSION")
+            .ok()
+            .or_else(|| fs::read_to_string(cache.join("session")).ok())
+            .map(|session| session.trim().to_string())
+            .filter(|session| !session.is_empty());
+        let url = env::var("AOC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+
+        Self {
+            remote: session.map(|session| Remote::new(url, session)),
+            cache,
+            bundled: true,
+        }
+    }
+
+    pub fn cached(&self, day: u8) -> PathBuf {
+        self.cache.join(format!("day{day:02}/input.txt"))
+    }
+
+    /// Never fetches anything.
+    pub fn offline() -> Self {
+        Self {
+            remote: None,
+            ..Self::from_env()
+        }
+    }
+
+    /// Where the input was found, and its text.
+    pub fn load(&self, day: u8) -> Result<(PathBuf, String), InputError> {
+        let path = self.cached(day);
+        if let Some(text) = read_if_exists(&path)? {
+            return Ok((path, text));
+        }
+
+        if let Some(remote) = &self.remote {
+            let text = remote.input(day)?;
+            let io = |error| InputError::Io(path.clone(), error);
+            fs::create_dir_all(path.parent().unwrap()).map_err(io)?;
+            fs::write(&path, &text).map_err(io)?;
+            return Ok((path, text));
+        }
+
+        if self.bundled {
+            let path = bundled(day);
+            if let Some(text) = read_if_exists(&path)? {
+                return Ok((path, text));
+            }
+        }
+
+        Err(InputError::Missing(day))
+    }
+}
+
+/// The input committed to this repository, if there is one.
+pub fn bundled(day: u8) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("../inputs/day{day:02}/input.txt"))
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>, InputError> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(InputError::Io(path.to_path_buf(), error)),
+    }
+}
+
+/// The ledger of right answers and wrong guesses lives next to its input, in `<input>.answers`.
+pub fn ledger_path(input: &Path) -> PathBuf {
+    input.with_extension("answers")
+}
+
+/// Params for an input other than the real one live next to it, in `<input>.params`.
+pub fn params_path(input: &Path) -> PathBuf {
+    input.with_extension("params")
+}
+
+/// No file means the defaults, for the real input.
+pub fn read_params(input: &Path) -> Result<String, InputError> {
+    Ok(read_if_exists(&params_path(input))?.unwrap_or_default())
+}
+
+/// No file means nothing known yet.
+pub fn read_ledger(input: &Path) -> Result<Ledger, InputError> {
+    let path = ledger_path(input);
+    let Some(text) = read_if_exists(&path)? else {
+        return Ok(Ledger::default());
+    };
+
+    Ledger::parse(&text).map_err(|error| InputError::Parse(path, error))
+}
+
+pub fn write_ledger(input: &Path, ledger: &Ledger) -> Result<(), InputError> {
+    let path = ledger_path(input);
+    fs::write(&path, ledger.to_string()).map_err(|error| InputError::Io(path, error))
+}
+
+/// For tests: The day's cached input and its ledger, if that knows every answer the bundled
+/// ledger knows. Else the bundled input and its ledger.
+pub fn local(day: u8) -> (String, Ledger) {
+    local_in(&Inputs::offline(), day)
+}
+
+fn local_in(inputs: &Inputs, day: u8) -> (String, Ledger) {
+    let bundled = bundled(day);
+    let bundled_ledger = read_ledger(&bundled).unwrap();
+
+    let cached = inputs.cached(day);
+    if let Some(input) = read_if_exists(&cached).unwrap() {
+        let ledger = read_ledger(&cached).unwrap();
+        if bundled_ledger
+            .answers
+            .keys()
+            .all(|part| ledger.answers.contains_key(part))
+        {
+            return (input, ledger);
+        }
+    }
+
+    let input = read_if_exists(&bundled)
+        .unwrap()
+        .unwrap_or_else(|| panic!("no input for day {day}"));
+    (input, bundled_ledger)
+}
+
+#[test]
+fn test_remote() {
+    use crate::ledger::Guess;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // A mock server that answers one request and hands back its head and body.
+    let serve = |response: &'static str| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/2024", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let head: Vec<String> = (&mut reader)
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .collect();
+            let length = head
+                .iter()
+                .find_map(|line| line.strip_prefix("Content-Length: "))
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+        (url, server)
+    };
+
+    let cache = env::temp_dir().join(format!("aoc-test-remote-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&cache);
+
+    let (url, server) = serve("HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n125 17\r\n");
+    let inputs = Inputs {
+        cache: cache.clone(),
+        remote: Some(Remote::new(url, "secret")),
+        bundled: false,
+    };
+    assert_eq!(
+        (cache.join("day11/input.txt"), "125 17\r\n".to_string()),
+        inputs.load(11).unwrap()
+    );
+    let (head, _) = server.join().unwrap();
+    assert_eq!("GET /2024/day/11/input HTTP/1.1", head[0]);
+    assert!(head.contains(&"Cookie: session=secret".to_string()));
+
+    let (url, server) = serve(
+        "HTTP/1.1 200 OK\r\n\r\n<article><p>That's not the right answer; \
+         your answer is too high.</p></article>",
+    );
+    let remote = Remote::new(url, "secret");
+    let verdict = remote.answer(11, 2, &Answer::from("6,1")).unwrap();
+    assert_eq!(Verdict::Wrong(Guess::TooHigh), verdict);
+    let (head, body) = server.join().unwrap();
+    assert_eq!("POST /2024/day/11/answer HTTP/1.1", head[0]);
+    assert_eq!("level=2&answer=6%2C1", body);
+
+    let (url, server) = serve("HTTP/1.1 400 Bad Request\r\n\r\nPlease log in.");
+    let inputs = Inputs {
+        remote: Some(Remote::new(url, "expired")),
+        ..inputs
+    };
+    let error = inputs.load(12).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .ends_with("/day/12/input: HTTP/1.1 400 Bad Request")
+    );
+    server.join().unwrap();
+
+    // Cached now, so the server is not needed any more.
+    let inputs = Inputs {
+        remote: None,
+        ..inputs
+    };
+    assert_eq!("125 17\r\n", inputs.load(11).unwrap().1);
+    assert!(matches!(inputs.load(12), Err(InputError::Missing(12))));
+    let inputs = Inputs {
+        bundled: true,
+        ..inputs
+    };
+    assert_eq!(bundled(1), inputs.load(1).unwrap().0);
+
+    let path = inputs.cached(11);
+    assert_eq!(Ledger::default(), read_ledger(&path).unwrap());
+    let mut ledger = Ledger::default();
+    ledger.record(1, Answer::Number(55312), &Verdict::Right);
+    write_ledger(&path, &ledger).unwrap();
+    assert_eq!(ledger, read_ledger(&path).unwrap());
+
+    fs::remove_dir_all(&cache).unwrap();
+}
+
+#[test]
+fn test_local() {
+    let cache = env::temp_dir().join(format!("aoc-test-local-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&cache);
+    let inputs = Inputs {
+        cache: cache.clone(),
+        remote: None,
+        bundled: true,
+    };
+    let bundled_input = fs::read_to_string(bundled(1)).unwrap();
+
+    // Fetched by `aoc run`, but no answers known yet.
+    let path = inputs.cached(1);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "1 2\n").unwrap();
+    assert_eq!(bundled_input, local_in(&inputs, 1).0);
+
+    let mut ledger = Ledger::default();
+    ledger.record(1, Answer::Number(1), &Verdict::Right);
+    write_ledger(&path, &ledger).unwrap();
+    assert_eq!(bundled_input, local_in(&inputs, 1).0);
+
+    ledger.record(2, Answer::Number(2), &Verdict::Right);
+    write_ledger(&path, &ledger).unwrap();
+    assert_eq!(("1 2\n".to_string(), ledger), local_in(&inputs, 1));
+
+    fs::remove_dir_all(&cache).unwrap();
+}
@@ -0,0 +1,246 @@
+//! What is known about the answers to one input: The right ones, and wrong guesses.
+
+use crate::{Answer, ParseError, Source};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Right answers by part.
+pub type Answers = BTreeMap<u8, Answer>;
+
+/// Why a guess was wrong. Too high or too low bound the right answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Guess {
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+
+impl fmt::Display for Guess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Wrong => write!(f, "wrong"),
+            Self::TooHigh => write!(f, "too high"),
+            Self::TooLow => write!(f, "too low"),
+        }
+    }
+}
+
+/// What the puzzle server made of a submitted answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    Wrong(Guess),
+    /// How long to wait, like `37s`, if the server said.
+    RateLimited(Option<String>),
+    /// The part was solved before, or is not unlocked yet.
+    WrongLevel,
+    /// The response, stripped of its markup, if it is none of the above.
+    Unknown(String),
+}
+
+impl Verdict {
+    pub fn parse(html: &str) -> Self {
+        if html.contains("That's the right answer") {
+            Self::Right
+        } else if html.contains("That's not the right answer") {
+            Self::Wrong(if html.contains("too high") {
+                Guess::TooHigh
+            } else if html.contains("too low") {
+                Guess::TooLow
+            } else {
+                Guess::Wrong
+            })
+        } else if html.contains("You gave an answer too recently") {
+            let wait = html
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| wait.to_string());
+            Self::RateLimited(wait)
+        } else if html.contains("You don't seem to be solving the right level") {
+            Self::WrongLevel
+        } else {
+            Self::Unknown(strip_tags(html))
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Right => write!(f, "right"),
+            Self::Wrong(guess) => write!(f, "{guess}"),
+            Self::RateLimited(Some(wait)) => write!(f, "rate limited, wait {wait}"),
+            Self::RateLimited(None) => write!(f, "rate limited"),
+            Self::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Self::Unknown(text) => write!(f, "unknown response: {text}"),
+        }
+    }
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ledger {
+    pub answers: Answers,
+    /// Part, answer and why it was wrong, oldest first.
+    pub guesses: Vec<(u8, Answer, Guess)>,
+}
+
+impl Ledger {
+    /// One entry per line: `part1: 5030` for a right answer, `part1 wrong: 5031`,
+    /// `part1 too high: 6000` or `part1 too low: 12` for a wrong guess.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let source = Source::new(text);
+        let mut ledger = Self::default();
+
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let (key, answer) = source.split_once(line, ": ")?;
+            let (part, guess) = key
+                .split_once(' ')
+                .map_or((key, None), |(part, guess)| (part, Some(guess)));
+            let part = source.number(source.strip_prefix(part, "part")?)?;
+            let Ok(answer) = answer.parse();
+
+            let guess = match guess {
+                None => {
+                    ledger.answers.insert(part, answer);
+                    continue;
+                }
+                Some("wrong") => Guess::Wrong,
+                Some("too high") => Guess::TooHigh,
+                Some("too low") => Guess::TooLow,
+                Some(guess) => {
+                    return Err(source.error(guess, "`wrong`, `too high` or `too low`"));
+                }
+            };
+            ledger.guesses.push((part, answer, guess));
+        }
+
+        Ok(ledger)
+    }
+
+    /// The right answer, for tests.
+    pub fn expected(&self, part: u8) -> Answer {
+        self.answers
+            .get(&part)
+            .unwrap_or_else(|| panic!("no known answer for part {part}"))
+            .clone()
+    }
+
+    /// Remembers what the server said. Verdicts that say nothing about the answer are dropped.
+    pub fn record(&mut self, part: u8, answer: Answer, verdict: &Verdict) {
+        match verdict {
+            Verdict::Right => {
+                self.answers.insert(part, answer);
+            }
+            Verdict::Wrong(guess) => self.guesses.push((part, answer, *guess)),
+            Verdict::RateLimited(_) | Verdict::WrongLevel | Verdict::Unknown(_) => (),
+        }
+    }
+
+    /// Why submitting `answer` for `part` would be pointless, if it would.
+    pub fn objection(&self, part: u8, answer: &Answer) -> Option<String> {
+        if let Some(right) = self.answers.get(&part) {
+            return Some(format!("part {part} is already solved: {right}"));
+        }
+
+        self.guesses
+            .iter()
+            .filter(|(p, ..)| *p == part)
+            .find_map(|(_, guess, why)| {
+                let out_of_bounds = match (answer, guess, why) {
+                    (Answer::Number(answer), Answer::Number(guess), Guess::TooHigh) => {
+                        answer >= guess
+                    }
+                    (Answer::Number(answer), Answer::Number(guess), Guess::TooLow) => {
+                        answer <= guess
+                    }
+                    _ => false,
+                };
+
+                (answer == guess || out_of_bounds)
+                    .then(|| format!("{answer} is wrong, {guess} was {why}"))
+            })
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (part, answer) in &self.answers {
+            writeln!(f, "part{part}: {answer}")?;
+        }
+        for (part, answer, guess) in &self.guesses {
+            writeln!(f, "part{part} {guess}: {answer}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_ledger() {
+    let text = "part1: 5030\npart2 too high: 2000\npart2 too low: 1000\npart2 wrong: 1500\n";
+    let mut ledger = Ledger::parse(text).unwrap();
+    assert_eq!(Answer::Number(5030), ledger.expected(1));
+    assert_eq!(text, ledger.to_string());
+
+    assert!(ledger.objection(1, &Answer::Number(5030)).is_some());
+    assert_eq!(None, ledger.objection(2, &Answer::Number(1928)));
+    assert_eq!(
+        Some("2000 is wrong, 2000 was too high".to_string()),
+        ledger.objection(2, &Answer::Number(2000))
+    );
+    assert_eq!(
+        Some("999 is wrong, 1000 was too low".to_string()),
+        ledger.objection(2, &Answer::Number(999))
+    );
+    assert!(ledger.objection(2, &Answer::Number(1500)).is_some());
+
+    ledger.record(2, Answer::Number(1928), &Verdict::RateLimited(None));
+    ledger.record(2, Answer::Number(1928), &Verdict::Right);
+    assert_eq!(Answer::Number(1928), ledger.expected(2));
+
+    let error = Ledger::parse("part1: 5030\npart2 maybe: 6\n").unwrap_err();
+    assert_eq!(
+        "2:7: expected `wrong`, `too high` or `too low`, found `maybe`",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_verdict() {
+    let verdict = |html| Verdict::parse(html).to_string();
+    assert_eq!(
+        "right",
+        verdict("<p>That's the right answer!  You are <em>one gold star</em> closer.</p>")
+    );
+    assert_eq!(
+        "too low",
+        verdict("<p>That's not the right answer; your answer is too low.</p>")
+    );
+    assert_eq!(
+        "wrong",
+        verdict("<p>That's not the right answer.  If you're stuck, ...</p>")
+    );
+    assert_eq!(
+        "rate limited, wait 37s",
+        verdict("<p>You gave an answer too recently.  You have 37s left to wait.</p>")
+    );
+    assert_eq!(
+        "unknown response: Oops, 500",
+        verdict("<html><b>Oops</b>,\n 500</html>")
+    );
+}
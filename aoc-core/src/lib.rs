@@ -0,0 +1,285 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+pub mod input;
+pub mod ledger;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                #[allow(clippy::cast_lossless)] // `usize` and `isize` have no `From`.
+                fn from(number: $t) -> Self {
+                    Self::Number(number as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+/// Reads an answer back from its `Display` form: Numbers become `Number`, anything else `Text`.
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(text: &str) -> Result<Self, Infallible> {
+        Ok(text
+            .parse()
+            .map_or_else(|_| Self::Text(text.to_string()), Self::Number))
+    }
+}
+
+/// Why and where the input could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Starting at 1.
+    pub line: usize,
+    /// Starting at 1, counting characters.
+    pub column: usize,
+    /// Empty if something is missing, for example at the end of a line.
+    pub token: String,
+    pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: expected {}, found ",
+            self.line, self.column, self.expected
+        )?;
+        if self.token.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "`{}`", self.token)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Why input could not be read from a reader.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// The input text, used to work out where a token came from when reporting errors.
+/// All tokens passed in must be slices of that text.
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+    text: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub const fn new(text: &'a str) -> Self {
+        Self { text }
+    }
+
+    pub fn error(self, token: &str, expected: impl Into<String>) -> ParseError {
+        let offset = token
+            .as_ptr()
+            .addr()
+            .wrapping_sub(self.text.as_ptr().addr());
+        assert!(
+            offset <= self.text.len(),
+            "token not from source: {token:?}"
+        );
+
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            token: token.to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Something expected right after `text` is missing.
+    pub fn missing(self, text: &str, expected: impl Into<String>) -> ParseError {
+        self.error(&text[text.len()..], expected)
+    }
+
+    pub fn number<T: FromStr>(self, token: &str) -> Result<T, ParseError> {
+        token.parse().map_err(|_| self.error(token, "a number"))
+    }
+
+    pub fn split_once(
+        self,
+        text: &'a str,
+        separator: &str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        text.split_once(separator)
+            .ok_or_else(|| self.missing(text, format!("`{separator}`")))
+    }
+
+    pub fn strip_prefix(self, text: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+        text.strip_prefix(prefix)
+            .ok_or_else(|| self.error(text, format!("`{prefix}`")))
+    }
+
+    /// The next token, which should be somewhere in `text`.
+    pub fn next(
+        self,
+        tokens: &mut impl Iterator<Item = &'a str>,
+        text: &'a str,
+        expected: &str,
+    ) -> Result<&'a str, ParseError> {
+        tokens.next().ok_or_else(|| self.missing(text, expected))
+    }
+
+    /// Nothing more is expected after the tokens read so far.
+    pub fn end(self, tokens: &mut impl Iterator<Item = &'a str>) -> Result<(), ParseError> {
+        tokens
+            .next()
+            .map_or(Ok(()), |token| Err(self.error(token, "end of line")))
+    }
+}
+
+/// Reads `key: value` lines, like `width: 11`, and hands each pair to `set`.
+pub fn settings(
+    text: &str,
+    mut set: impl FnMut(Source, &str, &str) -> Result<(), ParseError>,
+) -> Result<(), ParseError> {
+    let source = Source::new(text);
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        let (key, value) = source.split_once(line, ": ")?;
+        set(source, key, value)?;
+    }
+
+    Ok(())
+}
+
+/// One day's puzzle: Parse the input once, then solve either part.
+pub trait Solution {
+    /// Puzzle constants that are not part of the input, like grid sizes.
+    /// The default holds the values for the real puzzle input.
+    type Params: Default;
+
+    /// The parsed input, possibly borrowing from the input text.
+    type Input<'a>;
+
+    /// Day 25 only has one part.
+    const PARTS: u8 = 2;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError>;
+
+    fn part1(input: &Self::Input<'_>, params: &Self::Params) -> Answer;
+
+    fn part2(input: &Self::Input<'_>, params: &Self::Params) -> Answer;
+
+    /// Params for inputs other than the real one, like puzzle examples, from `key: value`
+    /// lines. Keys that are not given keep their default.
+    fn params(text: &str) -> Result<Self::Params, ParseError> {
+        settings(text, |source, key, _| Err(source.error(key, "no params")))?;
+        Ok(Self::Params::default())
+    }
+
+    /// Forget anything memoized by earlier runs, so that benchmarks measure a cold start.
+    fn reset() {}
+
+    /// Parse the input and solve the given part.
+    fn solve(input: &str, part: u8, params: &Self::Params) -> Result<Answer, ParseError> {
+        let input = Self::parse(input)?;
+        match part {
+            1 => Ok(Self::part1(&input, params)),
+            2 if Self::PARTS == 2 => Ok(Self::part2(&input, params)),
+            _ => panic!("no part {part}"),
+        }
+    }
+}
+
+#[test]
+fn test_answer() {
+    assert_eq!(Answer::Number(42), Answer::from(42_usize));
+    assert_eq!(Answer::Number(-1), Answer::from(-1_isize));
+    assert_eq!("6,1", Answer::from("6,1").to_string());
+    assert_eq!(
+        "218811774248729",
+        Answer::from(218811774248729_u64).to_string()
+    );
+    assert_eq!(Ok(Answer::Number(-7)), "-7".parse());
+    assert_eq!(Ok(Answer::from("6,1")), "6,1".parse());
+}
+
+#[test]
+fn test_parse_error() {
+    let input = "1 2\n3 x4\n";
+    let source = Source::new(input);
+    let line = input.lines().nth(1).unwrap();
+
+    let error = source.number::<usize>(&line[2..]).unwrap_err();
+    assert_eq!(
+        (2, 3, "x4"),
+        (error.line, error.column, error.token.as_str())
+    );
+    assert_eq!("2:3: expected a number, found `x4`", error.to_string());
+
+    let error = source.split_once(line, ",").unwrap_err();
+    assert_eq!("2:5: expected `,`, found nothing", error.to_string());
+
+    let error = source.strip_prefix(line, "4").unwrap_err();
+    assert_eq!("2:1: expected `4`, found `3 x4`", error.to_string());
+
+    let mut tokens = line.split_whitespace();
+    assert_eq!(Ok("3"), source.next(&mut tokens, line, "a number"));
+    assert!(source.end(&mut tokens).is_err());
+}
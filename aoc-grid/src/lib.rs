@@ -0,0 +1,332 @@
+use aoc_core::{ParseError, Source};
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+pub type Scalar = isize;
+
+/// A position on a grid, or an offset between two positions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[must_use]
+pub struct Point {
+    pub x: Scalar,
+    pub y: Scalar,
+}
+
+impl Point {
+    pub const fn new(x: Scalar, y: Scalar) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn manhattan(self) -> Scalar {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<Scalar> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: Scalar) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Add<Direction> for Point {
+    type Output = Self;
+
+    fn add(self, dir: Direction) -> Self {
+        self + dir.offset()
+    }
+}
+
+impl Sub<Direction> for Point {
+    type Output = Self;
+
+    fn sub(self, dir: Direction) -> Self {
+        self - dir.offset()
+    }
+}
+
+/// One of the eight compass directions, with `y` growing downwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[must_use]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// All eight directions, clockwise starting from `Up`.
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// The four horizontal and vertical directions, clockwise starting from `Up`.
+    pub const ORTHOGONAL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub const fn offset(self) -> Point {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::UpRight => Point::new(1, -1),
+            Self::Right => Point::new(1, 0),
+            Self::DownRight => Point::new(1, 1),
+            Self::Down => Point::new(0, 1),
+            Self::DownLeft => Point::new(-1, 1),
+            Self::Left => Point::new(-1, 0),
+            Self::UpLeft => Point::new(-1, -1),
+        }
+    }
+
+    /// Rotate 90 degrees clockwise.
+    pub const fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    /// Rotate 90 degrees counter-clockwise.
+    pub const fn turn_left(self) -> Self {
+        self.rotate(6)
+    }
+
+    pub const fn reverse(self) -> Self {
+        self.rotate(4)
+    }
+
+    // Rotate clockwise in steps of 45 degrees.
+    const fn rotate(self, steps: usize) -> Self {
+        Self::ALL[(self as usize + steps) % Self::ALL.len()]
+    }
+}
+
+/// A dense rectangular grid, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Parse one cell per character, one row per line. All rows must have the same length.
+    pub fn parse_with(
+        input: &str,
+        expected: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let source = Source::new(input);
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for line in input.lines() {
+            for (index, c) in line.char_indices() {
+                let token = &line[index..index + c.len_utf8()];
+                cells.push(cell(c).ok_or_else(|| source.error(token, expected))?);
+            }
+
+            let row_width = line.chars().count();
+            let width = *width.get_or_insert(row_width);
+            if row_width != width {
+                return Err(source.error(line, format!("a row of {width} cells")));
+            }
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.cell_index(point).is_some()
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.cell_index(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cell_index(point).map(|index| &mut self.cells[index])
+    }
+
+    /// All points, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> + use<T> {
+        let (width, height) = (self.width as Scalar, self.height as Scalar);
+        (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    /// All points with their values, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    /// Up to four horizontally or vertically adjacent points that lie on the grid.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &Direction::ORTHOGONAL)
+    }
+
+    /// Up to eight adjacent points, including diagonals, that lie on the grid.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &Direction::ALL)
+    }
+
+    fn neighbours(
+        &self,
+        point: Point,
+        directions: &'static [Direction],
+    ) -> impl Iterator<Item = Point> + '_ {
+        directions
+            .iter()
+            .map(move |&dir| point + dir)
+            .filter(|&next| self.contains(next))
+    }
+
+    /// First point, row by row, holding the given value.
+    pub fn find(&self, value: T) -> Option<Point>
+    where
+        T: Copy + PartialEq,
+    {
+        self.iter()
+            .find_map(|(point, cell)| (*cell == value).then_some(point))
+    }
+
+    /// Transform every cell, keeping the layout.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn cell_index(&self, point: Point) -> Option<usize> {
+        let x = usize::try_from(point.x).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(point.y).ok().filter(|&y| y < self.height)?;
+        Some(y * self.width + x)
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, "", Some)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{point:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{point:?} is outside the grid"))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parse() {
+    let grid = Grid::parse("ab\ncd\nef\n").unwrap();
+    assert_eq!((2, 3), (grid.width(), grid.height()));
+    assert_eq!('d', grid[Point::new(1, 1)]);
+    assert_eq!(Some(Point::new(0, 2)), grid.find('e'));
+    assert_eq!(None, grid.get(Point::new(2, 0)));
+    assert_eq!(None, grid.get(Point::new(0, -1)));
+    assert_eq!("ab\ncd\nef\n", grid.to_string());
+}
+
+#[test]
+fn test_parse_error() {
+    let error = Grid::parse("ab\nc\n").unwrap_err();
+    assert_eq!(
+        "2:1: expected a row of 2 cells, found `c`",
+        error.to_string()
+    );
+
+    let error = Grid::parse_with("12\n3x\n", "a digit", |c| c.to_digit(10)).unwrap_err();
+    assert_eq!("2:2: expected a digit, found `x`", error.to_string());
+}
+
+#[test]
+fn test_neighbours() {
+    let grid = Grid::new(3, 3, 0);
+    let corner = Point::new(0, 0);
+    let centre = Point::new(1, 1);
+    assert_eq!(2, grid.neighbours4(corner).count());
+    assert_eq!(3, grid.neighbours8(corner).count());
+    assert_eq!(4, grid.neighbours4(centre).count());
+    assert_eq!(8, grid.neighbours8(centre).count());
+}
+
+#[test]
+fn test_direction() {
+    assert_eq!(Direction::Right, Direction::Up.turn_right());
+    assert_eq!(Direction::Left, Direction::Up.turn_left());
+    assert_eq!(Direction::Up, Direction::Left.turn_right());
+    assert_eq!(Direction::DownRight, Direction::UpLeft.reverse());
+    assert_eq!(Point::new(2, 2), Point::new(1, 3) + Direction::UpRight);
+}
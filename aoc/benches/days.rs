@@ -0,0 +1,80 @@
+use aoc_core::Solution;
+use aoc_core::input::Inputs;
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+// Benchmarks parse, part 1 and part 2 of every day on its input, for example:
+//
+//     cargo bench -p aoc --bench days -- --save-baseline before
+//     cargo bench -p aoc --bench days -- --baseline before day06
+fn bench<S: Solution>(c: &mut Criterion, inputs: &Inputs, number: u8) {
+    let input = match inputs.load(number) {
+        Ok((_, input)) => input,
+        Err(error) => {
+            eprintln!("day{number:02}: skipped, {error}");
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group(format!("day{number:02}"));
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+
+    let parsed = S::parse(&input).unwrap();
+    let params = S::Params::default();
+    group.bench_function("part1", |b| {
+        b.iter_batched(
+            S::reset,
+            |()| S::part1(black_box(&parsed), &params),
+            BatchSize::PerIteration,
+        );
+    });
+    if S::PARTS == 2 {
+        group.bench_function("part2", |b| {
+            b.iter_batched(
+                S::reset,
+                |()| S::part2(black_box(&parsed), &params),
+                BatchSize::PerIteration,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    let inputs = Inputs::from_env();
+
+    bench::<day01::Day01>(c, &inputs, 1);
+    bench::<day02::Day02>(c, &inputs, 2);
+    bench::<day03::Day03>(c, &inputs, 3);
+    bench::<day04::Day04>(c, &inputs, 4);
+    bench::<day05::Day05>(c, &inputs, 5);
+    bench::<day06::Day06>(c, &inputs, 6);
+    bench::<day07::Day07>(c, &inputs, 7);
+    bench::<day08::Day08>(c, &inputs, 8);
+    bench::<day09::Day09>(c, &inputs, 9);
+    bench::<day10::Day10>(c, &inputs, 10);
+    bench::<day11::Day11>(c, &inputs, 11);
+    bench::<day12::Day12>(c, &inputs, 12);
+    bench::<day13::Day13>(c, &inputs, 13);
+    bench::<day14::Day14>(c, &inputs, 14);
+    bench::<day15::Day15>(c, &inputs, 15);
+    bench::<day16::Day16>(c, &inputs, 16);
+    bench::<day17::Day17>(c, &inputs, 17);
+    bench::<day18::Day18>(c, &inputs, 18);
+    bench::<day19::Day19>(c, &inputs, 19);
+    bench::<day20::Day20>(c, &inputs, 20);
+    bench::<day21::Day21>(c, &inputs, 21);
+    bench::<day22::Day22>(c, &inputs, 22);
+    bench::<day23::Day23>(c, &inputs, 23);
+    bench::<day24::Day24>(c, &inputs, 24);
+    bench::<day25::Day25>(c, &inputs, 25);
+}
+
+// Some parts take half a second, so keep the whole suite to a few minutes.
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = days
+}
+criterion_main!(benches);
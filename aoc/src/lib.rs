@@ -0,0 +1,121 @@
+use aoc_core::input;
+use aoc_core::{Answer, ParseError, Solution};
+use std::path::Path;
+
+pub mod regression;
+
+/// Which file could not be parsed: The input, or its params.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    Input(ParseError),
+    Params(ParseError),
+}
+
+impl SolveError {
+    /// Where it went wrong, for an input read from `path`.
+    pub fn at(&self, path: &Path) -> String {
+        match self {
+            Self::Input(error) => format!("{}:{error}", path.display()),
+            Self::Params(error) => format!("{}:{error}", input::params_path(path).display()),
+        }
+    }
+}
+
+/// A registered day.
+pub struct Day {
+    pub number: u8,
+    pub parts: u8,
+    solve: fn(&str, u8, &str) -> Result<Answer, SolveError>,
+}
+
+impl Day {
+    const fn new<S: Solution>(number: u8) -> Self {
+        Self {
+            number,
+            parts: S::PARTS,
+            solve: solve::<S>,
+        }
+    }
+
+    /// Solved with the puzzle constants for the real input.
+    pub fn solve(&self, input: &str, part: u8) -> Result<Answer, ParseError> {
+        self.solve_with(input, part, "")
+            .map_err(|error| match error {
+                SolveError::Input(error) => error,
+                SolveError::Params(_) => unreachable!("no params given"),
+            })
+    }
+
+    /// Solved with params from `key: value` lines, see `Solution::params`.
+    pub fn solve_with(&self, input: &str, part: u8, params: &str) -> Result<Answer, SolveError> {
+        (self.solve)(input, part, params)
+    }
+}
+
+fn solve<S: Solution>(input: &str, part: u8, params: &str) -> Result<Answer, SolveError> {
+    let params = S::params(params).map_err(SolveError::Params)?;
+    S::solve(input, part, &params).map_err(SolveError::Input)
+}
+
+pub const DAYS: [Day; 25] = [
+    Day::new::<day01::Day01>(1),
+    Day::new::<day02::Day02>(2),
+    Day::new::<day03::Day03>(3),
+    Day::new::<day04::Day04>(4),
+    Day::new::<day05::Day05>(5),
+    Day::new::<day06::Day06>(6),
+    Day::new::<day07::Day07>(7),
+    Day::new::<day08::Day08>(8),
+    Day::new::<day09::Day09>(9),
+    Day::new::<day10::Day10>(10),
+    Day::new::<day11::Day11>(11),
+    Day::new::<day12::Day12>(12),
+    Day::new::<day13::Day13>(13),
+    Day::new::<day14::Day14>(14),
+    Day::new::<day15::Day15>(15),
+    Day::new::<day16::Day16>(16),
+    Day::new::<day17::Day17>(17),
+    Day::new::<day18::Day18>(18),
+    Day::new::<day19::Day19>(19),
+    Day::new::<day20::Day20>(20),
+    Day::new::<day21::Day21>(21),
+    Day::new::<day22::Day22>(22),
+    Day::new::<day23::Day23>(23),
+    Day::new::<day24::Day24>(24),
+    Day::new::<day25::Day25>(25),
+];
+
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+#[test]
+fn test_registry() {
+    assert!(DAYS.iter().map(|day| day.number).eq(1..=25));
+    assert_eq!(1, day(25).unwrap().parts);
+    assert!(day(26).is_none());
+
+    let example = include_str!("../../inputs/day06/example.txt");
+    assert_eq!(Ok(Answer::Number(41)), day(6).unwrap().solve(example, 1));
+    assert_eq!(Ok(Answer::Number(6)), day(6).unwrap().solve(example, 2));
+
+    let error = day(6).unwrap().solve("...\n..\n", 1).unwrap_err();
+    assert_eq!(
+        "2:1: expected a row of 3 cells, found `..`",
+        error.to_string()
+    );
+
+    let example = include_str!("../../inputs/day14/example.txt");
+    let answer = day(14)
+        .unwrap()
+        .solve_with(example, 1, "width: 11\nheight: 7\n");
+    assert_eq!(Ok(Answer::Number(12)), answer);
+    let error = day(6)
+        .unwrap()
+        .solve_with(example, 1, "width: 11\n")
+        .unwrap_err();
+    assert_eq!(
+        "example.params:1:1: expected no params, found `width`",
+        error.at(Path::new("example.txt"))
+    );
+}
@@ -0,0 +1,235 @@
+use aoc::{DAYS, Day, day};
+use aoc_core::input::{self, Inputs};
+use aoc_core::ledger::Verdict;
+use std::env;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage: aoc run <day|all> [part] [--input <path>] [--no-bundled]
+       aoc submit <day> <part> [--input <path>]";
+
+struct Command {
+    submit: bool,
+    days: Vec<&'static Day>,
+    part: Option<u8>,
+    input: Option<PathBuf>,
+    bundled: bool,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = parse_args(&args).and_then(|command| {
+        if command.submit {
+            submit(&command)
+        } else {
+            run(&command)
+        }
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: &Command) -> Result<(), String> {
+    let inputs = Inputs {
+        bundled: command.bundled,
+        ..Inputs::from_env()
+    };
+    let mut total = Duration::ZERO;
+    let mut wrong = 0;
+
+    for day in &command.days {
+        let number = day.number;
+        let (path, input) = match load(command, &inputs, number) {
+            Ok(loaded) => loaded,
+            // Not everyone has every input, so keep going when running all days.
+            Err(error) if command.days.len() > 1 => {
+                println!("day{number:02}: skipped, {error}");
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let params = input::read_params(&path).map_err(|error| error.to_string())?;
+        let answers = input::read_ledger(&path)
+            .map_err(|error| error.to_string())?
+            .answers;
+
+        for part in 1..=day.parts {
+            if command.part.is_some_and(|wanted| wanted != part) {
+                continue;
+            }
+
+            let start = Instant::now();
+            let answer = day
+                .solve_with(&input, part, &params)
+                .map_err(|error| error.at(&path))?;
+            let elapsed = start.elapsed();
+            total += elapsed;
+
+            print!("day{number:02} part {part}: {answer} ({elapsed:.2?})");
+            match answers.get(&part) {
+                Some(expected) if *expected != answer => {
+                    println!(", expected {expected}");
+                    wrong += 1;
+                }
+                _ => println!(),
+            }
+        }
+    }
+
+    if command.days.len() > 1 {
+        println!("total: {total:.2?}");
+    }
+
+    match wrong {
+        0 => Ok(()),
+        1 => Err("1 answer differs from the expected one".to_string()),
+        _ => Err(format!("{wrong} answers differ from the expected ones")),
+    }
+}
+
+/// Solves the day, then submits the answer unless the ledger already rules it out.
+fn submit(command: &Command) -> Result<(), String> {
+    let inputs = Inputs::from_env();
+    let day = command.days[0];
+    let part = command.part.unwrap();
+
+    let (path, input) = load(command, &inputs, day.number)?;
+    let params = input::read_params(&path).map_err(|error| error.to_string())?;
+    let answer = day
+        .solve_with(&input, part, &params)
+        .map_err(|error| error.at(&path))?;
+    let mut ledger = input::read_ledger(&path).map_err(|error| error.to_string())?;
+    if let Some(objection) = ledger.objection(part, &answer) {
+        return Err(format!("not submitting: {objection}"));
+    }
+
+    let remote = inputs
+        .remote
+        .as_ref()
+        .ok_or("set AOC_SESSION to submit answers")?;
+    let verdict = remote
+        .answer(day.number, part, &answer)
+        .map_err(|error| error.to_string())?;
+    ledger.record(part, answer.clone(), &verdict);
+    input::write_ledger(&path, &ledger).map_err(|error| error.to_string())?;
+
+    let message = format!("day{:02} part {part}: {answer}, {verdict}", day.number);
+    if verdict == Verdict::Right {
+        println!("{message}");
+        Ok(())
+    } else {
+        Err(message)
+    }
+}
+
+/// The input given on the command line, else the cached, fetched or bundled one.
+fn load(command: &Command, inputs: &Inputs, day: u8) -> Result<(PathBuf, String), String> {
+    command.input.as_ref().map_or_else(
+        || inputs.load(day).map_err(|error| error.to_string()),
+        |path| {
+            fs::read_to_string(path)
+                .map(|input| (path.clone(), input))
+                .map_err(|error| format!("{}: {error}", path.display()))
+        },
+    )
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter().map(String::as_str);
+
+    let submit = match args.next() {
+        Some("run") => false,
+        Some("submit") => true,
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let days = match args.next() {
+        Some("all") if !submit => DAYS.iter().collect(),
+        Some(arg) => {
+            let number = parse_number(arg, "day", 1..=25)?;
+            Vec::from([day(number).unwrap()])
+        }
+        None => return Err(USAGE.to_string()),
+    };
+
+    let mut part = None;
+    let mut input = None;
+    let mut bundled = true;
+    while let Some(arg) = args.next() {
+        match arg {
+            "--no-bundled" => bundled = false,
+            "--input" if days.len() == 1 => {
+                input = Some(PathBuf::from(args.next().ok_or(USAGE)?));
+            }
+            "--input" => return Err("--input needs a single day".to_string()),
+            _ if part.is_none() => part = Some(parse_number(arg, "part", 1..=days[0].parts)?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    if submit && part.is_none() {
+        return Err(USAGE.to_string());
+    }
+
+    Ok(Command {
+        submit,
+        days,
+        part,
+        input,
+        bundled,
+    })
+}
+
+fn parse_number(arg: &str, what: &str, valid: RangeInclusive<u8>) -> Result<u8, String> {
+    arg.parse()
+        .ok()
+        .filter(|number| valid.contains(number))
+        .ok_or_else(|| format!("invalid {what}: {arg}"))
+}
+
+#[test]
+fn test_parse_args() {
+    let parse = |args: &str| {
+        parse_args(
+            &args
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let command = parse("run 7 2 --input foo.txt").unwrap();
+    assert_eq!(7, command.days[0].number);
+    assert_eq!(Some(2), command.part);
+    assert_eq!(Some(PathBuf::from("foo.txt")), command.input);
+    assert!(command.bundled);
+
+    let command = parse("run all").unwrap();
+    assert_eq!(25, command.days.len());
+    assert_eq!(None, command.part);
+
+    let command = parse("run all --no-bundled").unwrap();
+    assert!(!command.bundled);
+
+    assert!(parse("").is_err());
+    assert!(parse("run 26").is_err());
+    assert!(parse("run 25 2").is_err());
+    assert!(parse("run 7 3").is_err());
+    assert!(parse("run all --input foo.txt").is_err());
+
+    let command = parse("submit 22 1").unwrap();
+    assert!(command.submit);
+    assert_eq!(Some(1), command.part);
+    assert!(parse("submit 22").is_err());
+    assert!(parse("submit all 1").is_err());
+}
@@ -0,0 +1,116 @@
+//! Checks every solver against every input that comes with known answers.
+
+use crate::{Day, day};
+use aoc_core::input::{self, InputError};
+use aoc_core::ledger::Answers;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// One input file, and the answers it should give.
+pub struct Case {
+    pub day: &'static Day,
+    pub path: PathBuf,
+    /// From `<name>.params`, empty for the real input.
+    pub params: String,
+    pub answers: Answers,
+}
+
+/// Every `<root>/dayNN/<name>.txt` that has a `<name>.answers` next to it.
+pub fn cases(root: &Path) -> Result<Vec<Case>, InputError> {
+    let mut cases = Vec::new();
+
+    for dir in sorted_entries(root)? {
+        let Some(day) = dir
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("day")?.parse().ok())
+            .and_then(day)
+        else {
+            continue;
+        };
+
+        for path in sorted_entries(&dir)? {
+            if path.extension().is_some_and(|extension| extension == "txt")
+                && input::ledger_path(&path).exists()
+            {
+                let params = input::read_params(&path)?;
+                let answers = input::read_ledger(&path)?.answers;
+                cases.push(Case {
+                    day,
+                    path,
+                    params,
+                    answers,
+                });
+            }
+        }
+    }
+
+    Ok(cases)
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, InputError> {
+    let io = |error| InputError::Io(dir.to_path_buf(), error);
+    let mut paths = fs::read_dir(dir)
+        .map_err(io)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io)?;
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Solves each part the case has an answer for, and describes what went wrong.
+pub fn check(case: &Case) -> Vec<String> {
+    let path = case.path.display();
+    let input = match fs::read_to_string(&case.path) {
+        Ok(input) => input,
+        Err(error) => return vec![format!("{path}: {error}")],
+    };
+
+    case.answers
+        .iter()
+        .filter_map(|(&part, expected)| {
+            if part == 0 || part > case.day.parts {
+                return Some(format!(
+                    "{path}: day {} has no part {part}",
+                    case.day.number
+                ));
+            }
+
+            match case.day.solve_with(&input, part, &case.params) {
+                Ok(answer) if answer == *expected => None,
+                Ok(answer) => Some(format!(
+                    "{path}: part {part} is {answer}, expected {expected}"
+                )),
+                Err(error) => Some(error.at(&case.path)),
+            }
+        })
+        .collect()
+}
+
+/// Checks all cases in parallel.
+pub fn check_all(cases: &[Case]) -> Vec<String> {
+    thread::scope(|scope| {
+        #[allow(clippy::needless_collect)] // Start all threads before joining any.
+        let threads: Vec<_> = cases
+            .iter()
+            .map(|case| scope.spawn(|| check(case)))
+            .collect();
+
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect()
+    })
+}
+
+#[test]
+fn test_inputs() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../inputs");
+    let cases = cases(&root).unwrap();
+    assert!(cases.len() > 25);
+
+    let failures = check_all(&cases);
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
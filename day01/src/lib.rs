@@ -1,46 +1,190 @@
-pub fn part1() -> usize {
-    let (mut list1, mut list2) = parse();
+use aoc_core::{Answer, ParseError, ReadError, Solution, Source};
+use std::collections::BTreeMap;
+use std::io::BufRead;
 
-    list1.sort_unstable();
-    list2.sort_unstable();
+type Location = usize;
+type Count = usize;
+type Histogram = BTreeMap<Location, Count>;
 
-    list1
-        .iter()
-        .zip(list2.iter())
-        .map(|(&x1, &x2)| x1.abs_diff(x2))
-        .sum()
+/// How often each location appears, one histogram per column.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Columns {
+    pub histograms: Vec<Histogram>,
+}
+
+/// How two columns compare.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    /// Pair the smallest locations, then the second smallest and so on, and add up the distances.
+    pub distance: usize,
+    /// Each location in the left column times how often it appears in the right column, added up.
+    pub similarity: usize,
+}
+
+impl Columns {
+    /// `None` if either column does not exist.
+    pub fn compare(&self, left: usize, right: usize) -> Option<Comparison> {
+        let left = self.histograms.get(left)?;
+        let right = self.histograms.get(right)?;
+
+        Some(Comparison {
+            distance: distance(left, right),
+            similarity: similarity(left, right),
+        })
+    }
 }
 
-pub fn part2() -> usize {
-    let (list1, list2) = parse();
+// Works through both columns in sorted order, a run of equal locations at a time.
+fn distance(left: &Histogram, right: &Histogram) -> usize {
+    let mut left_runs = left.iter().map(|(&location, &count)| (location, count));
+    let mut right_runs = right.iter().map(|(&location, &count)| (location, count));
+    let mut left = left_runs.next();
+    let mut right = right_runs.next();
+    let mut total = 0;
+
+    while let (Some((x1, n1)), Some((x2, n2))) = (left, right) {
+        let n = n1.min(n2);
+        total += n * x1.abs_diff(x2);
+
+        left = if n1 == n {
+            left_runs.next()
+        } else {
+            Some((x1, n1 - n))
+        };
+        right = if n2 == n {
+            right_runs.next()
+        } else {
+            Some((x2, n2 - n))
+        };
+    }
+
+    total
+}
 
-    // Input is only 1000 lines, so don't bother builing a histogram map.
-    list1
-        .iter()
-        .map(|&x1| x1 * list2.iter().filter(|&&x2| x1 == x2).count())
+fn similarity(left: &Histogram, right: &Histogram) -> usize {
+    left.iter()
+        .map(|(location, count)| location * count * right.get(location).unwrap_or(&0))
         .sum()
 }
 
-fn parse() -> (Vec<usize>, Vec<usize>) {
-    let mut list1 = Vec::new();
-    let mut list2 = Vec::new();
+/// Builds the histograms in one pass, one line at a time. The first line decides
+/// how many columns there are.
+pub fn read(mut reader: impl BufRead) -> Result<Columns, ReadError> {
+    let mut histograms: Vec<Histogram> = Vec::new();
+    let mut line = String::new();
+
+    for number in 1.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
 
-    for line in include_str!("input.txt").lines() {
-        let mut parts = line.split_whitespace();
-        list1.push(parts.next().unwrap().parse().unwrap());
-        list2.push(parts.next().unwrap().parse().unwrap());
-        assert!(parts.next().is_none());
+        let row = line.trim_end();
+        let source = Source::new(row);
+        let at_line = |error: ParseError| ParseError {
+            line: number,
+            ..error
+        };
+
+        let mut tokens = row.split_whitespace();
+        if number == 1 {
+            histograms.resize(tokens.clone().count(), Histogram::new());
+        }
+        for histogram in &mut histograms {
+            let token = source.next(&mut tokens, row, "a number").map_err(at_line)?;
+            let location = source.number(token).map_err(at_line)?;
+            *histogram.entry(location).or_default() += 1;
+        }
+        source.end(&mut tokens).map_err(at_line)?;
     }
 
-    (list1, list2)
+    Ok(Columns { histograms })
+}
+
+pub fn part1(columns: &Columns) -> usize {
+    columns.compare(0, 1).unwrap().distance
+}
+
+pub fn part2(columns: &Columns) -> usize {
+    columns.compare(0, 1).unwrap().similarity
+}
+
+/// The puzzle needs at least two columns.
+pub fn parse(input: &str) -> Result<Columns, ParseError> {
+    let columns = read(input.as_bytes()).map_err(|error| match error {
+        ReadError::Parse(error) => error,
+        ReadError::Io(error) => unreachable!("reading a string: {error}"),
+    })?;
+
+    if columns.histograms.len() < 2 {
+        let first = input.lines().next().unwrap_or_default();
+        return Err(Source::new(input).missing(first.trim_end(), "a number"));
+    }
+
+    Ok(columns)
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Params = ();
+    type Input<'a> = Columns;
+
+    fn parse(input: &str) -> Result<Columns, ParseError> {
+        parse(input)
+    }
+
+    fn part1(columns: &Columns, (): &()) -> Answer {
+        part1(columns).into()
+    }
+
+    fn part2(columns: &Columns, (): &()) -> Answer {
+        part2(columns).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(936063, part1());
+    let (input, ledger) = aoc_core::input::local(1);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(23150395, part2());
+    let (input, ledger) = aoc_core::input::local(1);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("3   4\n4   x\n").unwrap_err();
+    assert_eq!("2:5: expected a number, found `x`", error.to_string());
+    let error = parse("3   4\n4\n").unwrap_err();
+    assert_eq!("2:2: expected a number, found nothing", error.to_string());
+    let error = parse("3   4   5\n3   4\n").unwrap_err();
+    assert_eq!("2:6: expected a number, found nothing", error.to_string());
+    let error = parse("3\n4\n").unwrap_err();
+    assert_eq!("1:2: expected a number, found nothing", error.to_string());
+}
+
+#[test]
+fn test_columns() {
+    let columns = read("3 4 1\n4 3 1\n2 5 2\n1 3 3\n3 9 3\n3 3 3\n".as_bytes()).unwrap();
+    assert_eq!(3, columns.histograms.len());
+
+    let comparison = |left, right| {
+        columns
+            .compare(left, right)
+            .map(|comparison| (comparison.distance, comparison.similarity))
+    };
+    assert_eq!(Some((11, 31)), comparison(0, 1));
+    assert_eq!(Some((3, 31)), comparison(0, 2));
+    assert_eq!(Some((14, 27)), comparison(1, 2));
+    assert_eq!(None, comparison(0, 3));
 }
@@ -1,40 +1,246 @@
-use std::cmp::Reverse;
+use aoc_core::{Answer, ParseError, Solution, Source};
+use std::ops::RangeInclusive;
 
-pub fn solve(may_fix: bool) -> usize {
-    include_str!("input.txt")
+type Level = usize;
+type Report = Vec<Level>;
+type Band = RangeInclusive<Level>;
+
+/// What is wrong with the step from the level at the index to the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Goes the other way than the steps before it.
+    DirectionChange(usize),
+    StepTooSmall(usize),
+    StepTooLarge(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Safe,
+    /// The first fault, and every smallest set of indices of levels whose removal makes
+    /// the report safe.
+    Fixable(Fault, Vec<Vec<usize>>),
+    /// The first fault. More levels would have to be removed than allowed.
+    Unsafe(Fault),
+}
+
+/// Steps must stay within `band` and go the same way. Takes linear time for a fixed
+/// number of allowed `removals`, plus the time to list the sets.
+pub fn diagnose(levels: &[Level], band: &Band, removals: usize) -> Verdict {
+    let Some(fault) = first_fault(levels, band) else {
+        return Verdict::Safe;
+    };
+
+    let mut fixes: Vec<Vec<usize>> = [true, false]
+        .into_iter()
+        .flat_map(|increasing| fewest_removals(levels, band, removals, increasing))
+        .collect();
+    let Some(fewest) = fixes.iter().map(Vec::len).min() else {
+        return Verdict::Unsafe(fault);
+    };
+
+    // A set can work both ways, when it leaves no step that is not flat.
+    fixes.retain(|removed| removed.len() == fewest);
+    fixes.sort_unstable();
+    fixes.dedup();
+    Verdict::Fixable(fault, fixes)
+}
+
+fn first_fault(levels: &[Level], band: &Band) -> Option<Fault> {
+    // The first step that is not flat decides the direction.
+    let mut increasing = None;
+
+    for (index, pair) in levels.windows(2).enumerate() {
+        let diff = pair[0].abs_diff(pair[1]);
+        if diff < *band.start() {
+            return Some(Fault::StepTooSmall(index));
+        }
+        if diff > *band.end() {
+            return Some(Fault::StepTooLarge(index));
+        }
+        if diff != 0 && *increasing.get_or_insert(pair[0] < pair[1]) != (pair[0] < pair[1]) {
+            return Some(Fault::DirectionChange(index));
+        }
+    }
+
+    None
+}
+
+// No more than `removals` levels can lie between two kept ones, so finding the fewest
+// removals is O(n * removals). `best[i]` is the fewest removals before level `i` if it is
+// kept. Then every way to get there is followed back, for every smallest set.
+fn fewest_removals(
+    levels: &[Level],
+    band: &Band,
+    removals: usize,
+    increasing: bool,
+) -> Vec<Vec<usize>> {
+    let fits = |from: Level, to: Level| {
+        band.contains(&from.abs_diff(to)) && (from == to || (from < to) == increasing)
+    };
+    // The kept levels that can come right before the one at `index`, or `None` if
+    // everything before it is removed, given `removed` removals before it.
+    let previous = |best: &[Option<usize>], index: usize, removed: usize| {
+        let first = (index == removed).then_some(None);
+        let others = (index.saturating_sub(removals + 1)..index).filter(move |&previous| {
+            best[previous].is_some_and(|before| before + index - previous - 1 == removed)
+                && fits(levels[previous], levels[index])
+        });
+        first
+            .into_iter()
+            .chain(others.map(Some))
+            .collect::<Vec<_>>()
+    };
+
+    let mut best: Vec<Option<usize>> = Vec::with_capacity(levels.len());
+    for index in 0..levels.len() {
+        // Either everything before is removed, or the previous kept level is close by.
+        let everything = (index <= removals).then_some(index);
+        let cost = (index.saturating_sub(removals + 1)..index)
+            .filter(|&previous| fits(levels[previous], levels[index]))
+            .filter_map(|previous| Some(best[previous]? + index - previous - 1))
+            .chain(everything)
+            .filter(|&removed| removed <= removals)
+            .min();
+        best.push(cost);
+    }
+
+    // The last kept level, with everything after it removed.
+    let ends: Vec<(usize, usize)> = best
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &cost)| Some((index, cost? + levels.len() - 1 - index)))
+        .filter(|&(_, removed)| removed <= removals)
+        .collect();
+    let Some(fewest) = ends.iter().map(|&(_, removed)| removed).min() else {
+        return Vec::new();
+    };
+
+    // Partial sets of kept levels, from the last one back.
+    let mut paths: Vec<Vec<usize>> = ends
+        .iter()
+        .filter(|&&(_, removed)| removed == fewest)
+        .map(|&(index, _)| vec![index])
+        .collect();
+    let mut fixes = Vec::new();
+    while let Some(path) = paths.pop() {
+        let index = *path.last().unwrap();
+        for before in previous(&best, index, best[index].unwrap()) {
+            match before {
+                None => fixes.push(
+                    (0..levels.len())
+                        .filter(|index| !path.contains(index))
+                        .collect(),
+                ),
+                Some(before) => paths.push([path.as_slice(), &[before]].concat()),
+            }
+        }
+    }
+
+    fixes
+}
+
+pub fn solve(reports: &[Report], band: &Band, removals: usize) -> usize {
+    reports
+        .iter()
+        .filter(|levels| !matches!(diagnose(levels, band, removals), Verdict::Unsafe(_)))
+        .count()
+}
+
+pub fn parse(input: &str) -> Result<Vec<Report>, ParseError> {
+    let source = Source::new(input);
+
+    input
         .lines()
         .map(str::split_whitespace)
-        .map(|levels| levels.map(|level| level.parse().unwrap()))
-        .map(Iterator::collect::<Vec<usize>>)
-        .filter(|levels| is_safe(levels) || may_fix && can_fix(levels))
-        .count()
+        .map(|levels| levels.map(|level| source.number(level)))
+        .map(Iterator::collect)
+        .collect()
 }
 
-fn can_fix(levels: &[usize]) -> bool {
-    (0..levels.len()).any(|index| {
-        let mut reduced_levels = levels.to_vec();
-        reduced_levels.remove(index);
-        is_safe(&reduced_levels)
-    })
+pub struct Params {
+    pub band: Band,
+    pub part1_removals: usize,
+    pub part2_removals: usize,
 }
 
-fn is_safe(levels: &[usize]) -> bool {
-    let sorted = levels.is_sorted() || levels.is_sorted_by_key(Reverse);
-    let diffs_in_range = levels
-        .iter()
-        .zip(levels.iter().skip(1))
-        .map(|(&level1, &level2)| level1.abs_diff(level2))
-        .all(|diff| (1..=3).contains(&diff));
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            band: 1..=3,
+            part1_removals: 0,
+            part2_removals: 1,
+        }
+    }
+}
+
+pub struct Day02;
 
-    sorted && diffs_in_range
+impl Solution for Day02 {
+    type Params = Params;
+    type Input<'a> = Vec<Report>;
+
+    fn parse(input: &str) -> Result<Vec<Report>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(reports: &Vec<Report>, params: &Params) -> Answer {
+        solve(reports, &params.band, params.part1_removals).into()
+    }
+
+    fn part2(reports: &Vec<Report>, params: &Params) -> Answer {
+        solve(reports, &params.band, params.part2_removals).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(483, solve(false));
+    let (input, ledger) = aoc_core::input::local(2);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), &(1..=3), 0))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(528, solve(true));
+    let (input, ledger) = aoc_core::input::local(2);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), &(1..=3), 1))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("7 6 4\n1 2 -7\n").unwrap_err();
+    assert_eq!("2:5: expected a number, found `-7`", error.to_string());
+}
+
+#[test]
+fn test_diagnose() {
+    let diagnose = |levels: &[Level], removals| diagnose(levels, &(1..=3), removals);
+    assert_eq!(Verdict::Safe, diagnose(&[7, 6, 4, 2, 1], 0));
+    assert_eq!(
+        Verdict::Unsafe(Fault::StepTooLarge(1)),
+        diagnose(&[1, 2, 7, 8, 9], 1)
+    );
+    // Removing either the 3 or the 2 does it.
+    assert_eq!(
+        Verdict::Fixable(Fault::DirectionChange(1), vec![vec![1], vec![2]]),
+        diagnose(&[1, 3, 2, 4, 5], 1)
+    );
+    assert_eq!(
+        Verdict::Fixable(Fault::StepTooSmall(2), vec![vec![2], vec![3]]),
+        diagnose(&[8, 6, 4, 4, 1], 1)
+    );
+    assert_eq!(
+        Verdict::Fixable(Fault::DirectionChange(1), vec![vec![0], vec![1]]),
+        diagnose(&[9, 10, 8, 7, 6], 1)
+    );
+    assert_eq!(
+        Verdict::Fixable(Fault::StepTooLarge(1), vec![vec![2, 3]]),
+        diagnose(&[1, 2, 9, 9, 3, 4], 2)
+    );
+    assert_eq!(Verdict::Safe, crate::diagnose(&[1, 1, 2], &(0..=3), 0));
 }
@@ -1,34 +1,528 @@
-use regex::Regex;
+use aoc_core::{Answer, ParseError, ReadError, Solution, Source};
+use std::io::{self, Read};
+use std::ops::Range;
 
-pub fn part1(conditional: bool) -> usize {
-    let re = Regex::new(r"do\(\)|don't\(\)|mul\(([0-9]+),([0-9]+)\)").unwrap();
+type Value = usize;
+type Span = Range<usize>;
 
-    let mut enabled = true;
-    let mut result = 0;
+/// A call like `mul(2,4)` and where it is in the input, in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub name: &'static str,
+    pub args: Vec<Value>,
+    pub span: Span,
+}
+
+/// Finds calls to the given names, with any number of arguments. Everything else is noise.
+pub fn lex(input: &str, names: &[&'static str]) -> Result<Vec<Token>, ParseError> {
+    let source = Source::new(input);
+    let (calls, _) = calls(input.as_bytes(), names, true);
+
+    calls
+        .into_iter()
+        .map(|(name, args, span)| {
+            let args = args
+                .into_iter()
+                .map(|arg| source.number(&input[arg]))
+                .collect::<Result<_, _>>()?;
+
+            Ok(Token { name, args, span })
+        })
+        .collect()
+}
+
+// A name, where its arguments are, and where it is.
+type Call = (&'static str, Vec<Span>, Span);
 
-    for captures in re.captures_iter(include_str!("input.txt")) {
-        match captures.get(0).unwrap().as_str() {
-            "do()" => enabled = true,
-            "don't()" => enabled = false,
-            _ if !conditional || enabled => {
-                let f1: usize = captures.get(1).unwrap().as_str().parse().unwrap();
-                let f2: usize = captures.get(2).unwrap().as_str().parse().unwrap();
+// Also returns how far it got. Unless this is the `last` of the input, that is before the
+// first call that might continue past the end.
+fn calls(input: &[u8], names: &[&'static str], last: bool) -> (Vec<Call>, usize) {
+    let mut calls = Vec::new();
+    let mut pos = 0;
 
-                result += f1 * f2;
+    'scan: while pos < input.len() {
+        for &name in names {
+            match call(input, pos, name) {
+                Match::Found(args, end) => {
+                    calls.push((name, args, pos..end));
+                    pos = end;
+                    continue 'scan;
+                }
+                Match::Incomplete if !last => break 'scan,
+                Match::Incomplete | Match::None => (),
+            }
+        }
+        pos += 1;
+    }
+
+    (calls, pos)
+}
+
+enum Match {
+    Found(Vec<Span>, usize),
+    /// The input ends before it is clear whether this is a call.
+    Incomplete,
+    None,
+}
+
+// `name(1,2,...)` at `pos`.
+fn call(input: &[u8], pos: usize, name: &str) -> Match {
+    let rest = &input[pos..];
+    if !rest.starts_with(name.as_bytes()) {
+        return if name.as_bytes().starts_with(rest) {
+            Match::Incomplete
+        } else {
+            Match::None
+        };
+    }
+
+    let mut end = pos + name.len();
+    match input.get(end) {
+        Some(b'(') => end += 1,
+        Some(_) => return Match::None,
+        None => return Match::Incomplete,
+    }
+    let mut args = Vec::new();
+    if input.get(end) == Some(&b')') {
+        return Match::Found(args, end + 1);
+    }
+
+    loop {
+        let start = end;
+        while input.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+        }
+        match input.get(end) {
+            None => return Match::Incomplete,
+            Some(_) if start == end => return Match::None,
+            Some(b',') => args.push(start..end),
+            Some(b')') => {
+                args.push(start..end);
+                return Match::Found(args, end + 1);
+            }
+            Some(_) => return Match::None,
+        }
+        end += 1;
+    }
+}
+
+// Line and column of a byte in input that is read a piece at a time.
+#[derive(Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    const START: Self = Self { line: 1, column: 1 };
+
+    fn advance(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if byte & 0xc0 != 0x80 {
+                // Not a continuation byte, so a new character.
+                self.column += 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: Value,
+}
+
+pub type Effect = fn(&mut Machine, &[Value]);
+
+#[derive(Clone, Copy)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub arity: usize,
+    /// Whether `don't()` turns it off.
+    pub gated: bool,
+    pub effect: Effect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// From a call that disabled the machine to the call that enabled it again,
+    /// or to the last call.
+    Disabled,
+    /// A call in a disabled region.
+    Gated,
+    WrongArity {
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Part of the input that did not count, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Skipped {
+    pub span: Span,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Run {
+    pub total: Value,
+    /// In input order, regions before the calls in them.
+    pub skipped: Vec<Skipped>,
+}
+
+pub struct Interpreter {
+    instructions: Vec<Instruction>,
+    /// Whether gated instructions are skipped while disabled.
+    conditional: bool,
+}
+
+impl Interpreter {
+    /// Knows `do()`, `don't()` and `mul(a,b)`.
+    pub fn new(conditional: bool) -> Self {
+        let mut interpreter = Self {
+            instructions: Vec::new(),
+            conditional,
+        };
+        interpreter
+            .register("do", 0, false, |machine, _| machine.enabled = true)
+            .register("don't", 0, false, |machine, _| machine.enabled = false)
+            .register("mul", 2, true, |machine, args| {
+                machine.total += args[0] * args[1];
+            });
+
+        interpreter
+    }
+
+    /// Adds an instruction, or replaces the one with the same name.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        arity: usize,
+        gated: bool,
+        effect: Effect,
+    ) -> &mut Self {
+        let instruction = Instruction {
+            name,
+            arity,
+            gated,
+            effect,
+        };
+        match self
+            .instructions
+            .iter_mut()
+            .find(|known| known.name == name)
+        {
+            Some(known) => *known = instruction,
+            None => self.instructions.push(instruction),
+        }
+
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.instructions
+            .iter()
+            .map(|instruction| instruction.name)
+            .collect()
+    }
+
+    pub fn lex(&self, input: &str) -> Result<Vec<Token>, ParseError> {
+        lex(input, &self.names())
+    }
+
+    pub fn run(&self, tokens: &[Token]) -> Run {
+        let mut execution = self.start();
+        for token in tokens {
+            execution.step(token);
+        }
+
+        execution.finish()
+    }
+
+    pub const fn start(&self) -> Execution<'_> {
+        Execution {
+            interpreter: self,
+            machine: Machine {
+                enabled: true,
+                total: 0,
+            },
+            skipped: Vec::new(),
+            region: None,
+            end: 0,
+        }
+    }
+
+    /// Same as lexing and running all of the input, but only keeps as much of it in
+    /// memory as a call that spans chunks needs. A chunk size of 0 reads a byte at a time.
+    pub fn scan(&self, mut reader: impl Read, chunk_size: usize) -> Result<Run, ReadError> {
+        let names = self.names();
+        let mut execution = self.start();
+        let mut chunk = vec![0; chunk_size.max(1)];
+        // What is left of the input read so far, where it starts, and its line and column.
+        let mut buffer = Vec::new();
+        let mut offset = 0;
+        let mut position = Position::START;
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                read => read?,
+            };
+            buffer.extend_from_slice(&chunk[..read]);
+            let last = read == 0;
+
+            let (calls, consumed) = calls(&buffer, &names, last);
+            for (name, args, span) in calls {
+                let args = args
+                    .into_iter()
+                    .map(|arg| number(&buffer, arg, position))
+                    .collect::<Result<_, _>>()?;
+                execution.step(&Token {
+                    name,
+                    args,
+                    span: offset + span.start..offset + span.end,
+                });
+            }
+
+            position.advance(&buffer[..consumed]);
+            buffer.drain(..consumed);
+            offset += consumed;
+            if last {
+                return Ok(execution.finish());
+            }
+        }
+    }
+}
+
+// Reports errors like `Source::number` would, for digits at `arg` in the buffer.
+fn number(buffer: &[u8], arg: Span, mut position: Position) -> Result<Value, ParseError> {
+    let token = str::from_utf8(&buffer[arg.clone()]).unwrap();
+
+    token.parse().map_err(|_| {
+        position.advance(&buffer[..arg.start]);
+        ParseError {
+            line: position.line,
+            column: position.column,
+            token: token.to_string(),
+            expected: "a number".to_string(),
+        }
+    })
+}
+
+/// Runs calls one at a time, as they are found.
+pub struct Execution<'a> {
+    interpreter: &'a Interpreter,
+    machine: Machine,
+    skipped: Vec<Skipped>,
+    // Where the current disabled region starts, and its index in `skipped`.
+    region: Option<(usize, usize)>,
+    // Of the last call.
+    end: usize,
+}
+
+impl Execution<'_> {
+    pub fn step(&mut self, token: &Token) {
+        let Some(instruction) = self
+            .interpreter
+            .instructions
+            .iter()
+            .find(|known| known.name == token.name)
+        else {
+            return;
+        };
+        let conditional = self.interpreter.conditional;
+        self.end = token.span.end;
+
+        let reason = if token.args.len() != instruction.arity {
+            Some(Reason::WrongArity {
+                expected: instruction.arity,
+                found: token.args.len(),
+            })
+        } else if conditional && instruction.gated && !self.machine.enabled {
+            Some(Reason::Gated)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            self.skipped.push(Skipped {
+                span: token.span.clone(),
+                reason,
+            });
+            return;
+        }
+
+        (instruction.effect)(&mut self.machine, &token.args);
+
+        if !conditional {
+            return;
+        }
+        match (self.machine.enabled, self.region) {
+            (false, None) => {
+                self.region = Some((token.span.start, self.skipped.len()));
+                self.skipped.push(Skipped {
+                    span: token.span.clone(),
+                    reason: Reason::Disabled,
+                });
+            }
+            (true, Some((start, index))) => {
+                self.skipped[index].span = start..token.span.start;
+                self.region = None;
             }
             _ => (),
         }
     }
 
-    result
+    pub fn finish(mut self) -> Run {
+        if let Some((start, index)) = self.region {
+            self.skipped[index].span = start..self.end;
+        }
+
+        Run {
+            total: self.machine.total,
+            skipped: self.skipped,
+        }
+    }
+}
+
+pub fn part1(tokens: &[Token], conditional: bool) -> Value {
+    Interpreter::new(conditional).run(tokens).total
+}
+
+pub fn parse(input: &str) -> Result<Vec<Token>, ParseError> {
+    Interpreter::new(true).lex(input)
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Params = ();
+    type Input<'a> = Vec<Token>;
+
+    fn parse(input: &str) -> Result<Vec<Token>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(tokens: &Vec<Token>, (): &()) -> Answer {
+        part1(tokens, false).into()
+    }
+
+    fn part2(tokens: &Vec<Token>, (): &()) -> Answer {
+        part1(tokens, true).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(187194524, part1(false));
+    let (input, ledger) = aoc_core::input::local(3);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap(), false))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(127092535, part1(true));
+    let (input, ledger) = aoc_core::input::local(3);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part1(&parse(&input).unwrap(), true))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    // Everything that is not an instruction is ignored, only overflows are errors.
+    let error = parse("xmul(2,4)\nmul(99999999999999999999,1)").unwrap_err();
+    assert_eq!(
+        "2:5: expected a number, found `99999999999999999999`",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_interpreter() {
+    let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    let tokens = parse(input).unwrap();
+    assert_eq!(6, tokens.len());
+    assert_eq!(1..9, tokens[0].span);
+
+    let run = Interpreter::new(true).run(&tokens);
+    assert_eq!(48, run.total);
+    let skipped = |start, end, reason| Skipped {
+        span: start..end,
+        reason,
+    };
+    assert_eq!(
+        vec![
+            skipped(20, 59, Reason::Disabled),
+            skipped(28, 36, Reason::Gated),
+            skipped(48, 57, Reason::Gated),
+        ],
+        run.skipped
+    );
+
+    let mut interpreter = Interpreter::new(false);
+    interpreter
+        .register("add", 2, true, |machine, args| {
+            machine.total += args[0] + args[1];
+        })
+        .register("fma", 3, true, |machine, args| {
+            machine.total += args[0] * args[1] + args[2];
+        });
+    let tokens = interpreter
+        .lex("add(1,2)mul(3,4)fma(2,3,4)mul(1,2,3)")
+        .unwrap();
+    let run = interpreter.run(&tokens);
+    assert_eq!(3 + 12 + 10, run.total);
+    assert_eq!(
+        vec![skipped(
+            26,
+            36,
+            Reason::WrongArity {
+                expected: 2,
+                found: 3
+            }
+        )],
+        run.skipped
+    );
+}
+
+#[test]
+fn test_scan() {
+    let (input, _) = aoc_core::input::local(3);
+    for conditional in [false, true] {
+        let interpreter = Interpreter::new(conditional);
+        let expected = interpreter.run(&parse(&input).unwrap());
+        for chunk_size in [0, 1, 7, 4096] {
+            let run = interpreter.scan(input.as_bytes(), chunk_size).unwrap();
+            assert_eq!(expected, run);
+        }
+    }
+
+    // Splits a call, or anything that looks like one, at every possible place.
+    let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))\n\
+                 mumul(1,2)do(don't(mul(123,4567)mul(1,2,3)don't()mul(1";
+    let interpreter = Interpreter::new(true);
+    let expected = interpreter.run(&parse(input).unwrap());
+    for split in 0..=input.len() {
+        let (head, tail) = input.as_bytes().split_at(split);
+        assert_eq!(expected, interpreter.scan(head.chain(tail), 4096).unwrap());
+    }
+
+    let input = "\u{e9}mul(1,2)\n\u{e9}mul(1,99999999999999999999)";
+    let error = parse(input).unwrap_err();
+    assert_eq!(
+        "2:8: expected a number, found `99999999999999999999`",
+        error.to_string()
+    );
+    for split in 0..=input.len() {
+        let (head, tail) = input.as_bytes().split_at(split);
+        match interpreter.scan(head.chain(tail), 4096) {
+            Err(ReadError::Parse(scan_error)) => assert_eq!(error, scan_error),
+            result => panic!("{result:?}"),
+        }
+    }
 }
@@ -1,64 +1,325 @@
-use std::collections::HashMap;
-
-pub fn part1(input: &str) -> usize {
-    let chars = parse(input);
-
-    let mut result = 0;
-    // Search from every point.
-    for (x, y) in chars.keys() {
-        // Search in eight directions (nine, really, but this does not matter).
-        for dx in -1..=1 {
-            for dy in -1..=1 {
-                // "XMAS" found at this point, in this direction?
-                let found = "XMAS".chars().enumerate().all(|(offset, c)| {
-                    let offset = offset as isize;
-                    chars.get(&(x + dx * offset, y + dy * offset)) == Some(&c)
-                });
+use aoc_core::{Answer, ParseError, Solution};
+use aoc_grid::{Direction, Grid, Point, Scalar};
+
+/// A word found in a straight line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordMatch {
+    /// Index into the words searched for.
+    pub word: usize,
+    pub start: Point,
+    pub direction: Direction,
+    /// Of each letter, in order.
+    pub points: Vec<Point>,
+}
+
+/// Finds words in all eight directions. With `wrap`, words continue on the other side
+/// of the grid.
+pub fn find_words(chars: &Grid<char>, words: &[&str], wrap: bool) -> Vec<WordMatch> {
+    let mut matches = Vec::new();
+
+    for start in chars.points() {
+        for (index, word) in words.iter().enumerate() {
+            for direction in Direction::ALL {
+                let points: Option<Vec<_>> = word
+                    .chars()
+                    .zip(0..)
+                    .map(|(c, offset)| {
+                        let point = start + direction.offset() * offset;
+                        let point = if wrap { wrapped(chars, point) } else { point };
+                        (chars.get(point) == Some(&c)).then_some(point)
+                    })
+                    .collect();
 
-                if found {
-                    result += 1;
+                if let Some(points) = points {
+                    matches.push(WordMatch {
+                        word: index,
+                        start,
+                        direction,
+                        points,
+                    });
                 }
             }
         }
     }
-    result
+
+    matches
 }
 
-pub fn part2(input: &str) -> usize {
-    let chars = parse(input);
+const fn wrapped(chars: &Grid<char>, point: Point) -> Point {
+    Point::new(
+        point.x.rem_euclid(chars.width() as Scalar),
+        point.y.rem_euclid(chars.height() as Scalar),
+    )
+}
 
-    let is_ms = |p1, p2| {
-        let mut chars = [chars.get(&p1), chars.get(&p2)];
-        chars.sort_unstable();
-        chars == [Some(&'M'), Some(&'S')]
-    };
+/// How a stencil is turned: First mirrored left to right if `reflected`, then rotated
+/// clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Orientation {
+    pub quarter_turns: u8,
+    pub reflected: bool,
+}
 
-    chars
-        .iter()
-        .filter_map(|(&(x, y), &c)| (c == 'A').then_some((x, y)))
-        .filter(|&(x, y)| is_ms((x - 1, y - 1), (x + 1, y + 1)))
-        .filter(|&(x, y)| is_ms((x - 1, y + 1), (x + 1, y - 1)))
-        .count()
+impl Orientation {
+    pub const ROTATIONS: [Self; 4] = [
+        Self::new(0, false),
+        Self::new(1, false),
+        Self::new(2, false),
+        Self::new(3, false),
+    ];
+
+    /// All rotations, then all rotations of the mirror image.
+    pub const ALL: [Self; 8] = [
+        Self::new(0, false),
+        Self::new(1, false),
+        Self::new(2, false),
+        Self::new(3, false),
+        Self::new(0, true),
+        Self::new(1, true),
+        Self::new(2, true),
+        Self::new(3, true),
+    ];
+
+    pub const fn new(quarter_turns: u8, reflected: bool) -> Self {
+        Self {
+            quarter_turns,
+            reflected,
+        }
+    }
 }
 
-fn parse(input: &str) -> HashMap<(isize, isize), char> {
-    let mut result = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            result.insert((x as isize, y as isize), c);
+/// A rectangular pattern of characters, where wildcards match anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct Stencil {
+    cells: Grid<Option<char>>,
+}
+
+impl Stencil {
+    /// One row per line.
+    pub fn parse(text: &str, wildcard: char) -> Result<Self, ParseError> {
+        let cells = Grid::parse_with(text, "", |c| Some((c != wildcard).then_some(c)))?;
+        Ok(Self { cells })
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let mut cells = self.cells.clone();
+        if orientation.reflected {
+            let width = cells.width() as Scalar;
+            cells = transformed(&cells, cells.width(), cells.height(), |p| {
+                Point::new(width - 1 - p.x, p.y)
+            });
         }
+        for _ in 0..orientation.quarter_turns % 4 {
+            let height = cells.height() as Scalar;
+            cells = transformed(&cells, cells.height(), cells.width(), |p| {
+                Point::new(height - 1 - p.y, p.x)
+            });
+        }
+
+        Self { cells }
+    }
+}
+
+// Moves each cell of `cells` to `to(point)` on a new grid.
+fn transformed(
+    cells: &Grid<Option<char>>,
+    width: usize,
+    height: usize,
+    to: impl Fn(Point) -> Point,
+) -> Grid<Option<char>> {
+    let mut result = Grid::new(width, height, None);
+    for (point, &cell) in cells.iter() {
+        result[to(point)] = cell;
     }
+
     result
 }
 
+/// A stencil found on the grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StencilMatch {
+    /// Index into the stencils searched for.
+    pub stencil: usize,
+    /// Where the top left corner of the oriented stencil is.
+    pub start: Point,
+    pub orientation: Orientation,
+    /// Of each cell that is not a wildcard, row by row.
+    pub points: Vec<Point>,
+}
+
+/// Tries each stencil in each of the orientations. Orientations that look the same as an
+/// earlier one, like all four rotations of a symmetric stencil, are only tried once.
+pub fn find_stencils(
+    chars: &Grid<char>,
+    stencils: &[Stencil],
+    orientations: &[Orientation],
+) -> Vec<StencilMatch> {
+    let mut variants: Vec<(usize, Orientation, Stencil)> = Vec::new();
+    for (index, stencil) in stencils.iter().enumerate() {
+        for &orientation in orientations {
+            let variant = stencil.oriented(orientation);
+            if !variants
+                .iter()
+                .any(|(other, _, known)| *other == index && *known == variant)
+            {
+                variants.push((index, orientation, variant));
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    for start in chars.points() {
+        for (index, orientation, variant) in &variants {
+            let points: Option<Vec<_>> = variant
+                .cells
+                .iter()
+                .filter_map(|(offset, cell)| cell.map(|c| (start + offset, c)))
+                .map(|(point, c)| (chars.get(point) == Some(&c)).then_some(point))
+                .collect();
+
+            if let Some(points) = points {
+                matches.push(StencilMatch {
+                    stencil: *index,
+                    start,
+                    orientation: *orientation,
+                    points,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+/// The grid with everything but the given points blanked out with `.`.
+pub fn render(chars: &Grid<char>, points: impl IntoIterator<Item = Point>) -> Grid<char> {
+    let mut rendered = chars.map(|_| '.');
+    for point in points {
+        rendered[point] = chars[point];
+    }
+
+    rendered
+}
+
+pub fn part1(chars: &Grid<char>) -> usize {
+    find_words(chars, &["XMAS"], false).len()
+}
+
+pub fn part2(chars: &Grid<char>) -> usize {
+    let x_mas = Stencil::parse("M.S\n.A.\nM.S\n", '.').unwrap();
+    find_stencils(chars, &[x_mas], &Orientation::ALL).len()
+}
+
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input)
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Params = ();
+    type Input<'a> = Grid<char>;
+
+    fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(chars: &Grid<char>, (): &()) -> Answer {
+        part1(chars).into()
+    }
+
+    fn part2(chars: &Grid<char>, (): &()) -> Answer {
+        part2(chars).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(18, part1(include_str!("example.txt")));
-    assert_eq!(2534, part1(include_str!("input.txt")));
+    assert_eq!(
+        18,
+        part1(&parse(include_str!("../../inputs/day04/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(4);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(9, part2(include_str!("example.txt")));
-    assert_eq!(1866, part2(include_str!("input.txt")));
+    assert_eq!(
+        9,
+        part2(&parse(include_str!("../../inputs/day04/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(4);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("XMAS\nXMA\n").unwrap_err();
+    assert_eq!(
+        "2:1: expected a row of 4 cells, found `XMA`",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_search() {
+    let chars = parse("..X...\n.SAMX.\n.A..A.\nXMAS.S\n.X....\n").unwrap();
+    let matches = find_words(&chars, &["XMAS"], false);
+    assert_eq!(4, matches.len());
+    assert_eq!(
+        "..X...\n.SAMX.\n.A..A.\nXMAS.S\n.X....\n",
+        render(&chars, matches.iter().flat_map(|m| m.points.clone())).to_string()
+    );
+    assert_eq!(
+        (Point::new(4, 1), Direction::Left),
+        (matches[1].start, matches[1].direction)
+    );
+
+    let chars = parse("ASXM\nABCD\n").unwrap();
+    let matches = find_words(&chars, &["XMAS"], true);
+    assert_eq!(1, matches.len());
+    assert_eq!(Direction::Right, matches[0].direction);
+    let matches = find_words(&chars, &["SAMX"], true);
+    assert_eq!(
+        vec![
+            Point::new(1, 0),
+            Point::new(0, 0),
+            Point::new(3, 0),
+            Point::new(2, 0)
+        ],
+        matches[0].points
+    );
+
+    let corner = Stencil::parse("AB\n?C\n", '?').unwrap();
+    assert_eq!(
+        Stencil::parse("?A\nCB\n", '?').unwrap(),
+        corner.oriented(Orientation::new(1, false))
+    );
+    assert_eq!(
+        Stencil::parse("BA\nC?\n", '?').unwrap(),
+        corner.oriented(Orientation::new(0, true))
+    );
+
+    let chars = parse("ABX\nXCB\nXXA\n").unwrap();
+    let matches = find_stencils(&chars, &[corner], &Orientation::ALL);
+    let found: Vec<_> = matches.iter().map(|m| (m.start, m.orientation)).collect();
+    assert_eq!(
+        vec![
+            (Point::new(0, 0), Orientation::new(0, false)),
+            (Point::new(1, 1), Orientation::new(1, true)),
+        ],
+        found
+    );
+    assert_eq!(
+        vec![Point::new(1, 1), Point::new(2, 1), Point::new(2, 2)],
+        matches[1].points
+    );
 }
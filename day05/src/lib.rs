@@ -1,59 +1,600 @@
-use std::cmp::Ordering::Equal;
-use std::cmp::Ordering::Less;
-use std::collections::HashSet;
+use aoc_core::{Answer, ParseError, Solution, Source};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
 
-pub fn part1(input: &str) -> usize {
-    let (pairs, updates) = parse(input);
+type Page = usize;
+type Pairs = HashSet<(Page, Page)>;
+type Updates = Vec<Vec<Page>>;
+type Input = (Pairs, Updates);
+
+/// The rules as a directed graph, with an edge from each page to the pages that must
+/// come after it.
+pub struct Rules {
+    after: HashMap<Page, HashSet<Page>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Order {
+    pub pages: Vec<Page>,
+    /// Neighbours in `pages` that no rule orders, so they could be swapped. Empty if the
+    /// order is unique.
+    pub ambiguities: Vec<(Page, Page)>,
+}
+
+/// Pages that must each come before the next one, and the last before the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<Page>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rules form a cycle:")?;
+        for (index, page) in self.0.iter().enumerate() {
+            let next = self.0[(index + 1) % self.0.len()];
+            write!(f, " {page}|{next}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Rules {
+    pub fn new(pairs: &Pairs) -> Self {
+        let mut after: HashMap<Page, HashSet<Page>> = HashMap::new();
+        for &(first, second) in pairs {
+            after.entry(first).or_default().insert(second);
+        }
+
+        Self { after }
+    }
+
+    pub fn must_precede(&self, first: Page, second: Page) -> bool {
+        self.after
+            .get(&first)
+            .is_some_and(|after| after.contains(&second))
+    }
+
+    /// Sorts the pages topologically, using only the rules between them. Pages that could
+    /// go either way keep the order they have in the update.
+    pub fn order(&self, pages: &[Page]) -> Result<Order, Cycle> {
+        let sorted: Vec<Page> = sort(pages, &self.successors(pages))?
+            .into_iter()
+            .map(|index| pages[index])
+            .collect();
+
+        let ambiguities = sorted
+            .windows(2)
+            .filter(|pair| !self.must_precede(pair[0], pair[1]))
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+
+        Ok(Order {
+            pages: sorted,
+            ambiguities,
+        })
+    }
+}
+
+/// How to repair an update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fix {
+    Move,
+    Remove,
+}
+
+/// One change to an update, and the `X|Y` rule that the page broke before.
+///
+/// A moved page can be out of place only by way of other pages, like 1 in `3,1` with rules `1|2` and
+/// `2|3`, and then there is no such rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// Put `page` right after `after`, or first if `None`.
+    Move {
+        page: Page,
+        after: Option<Page>,
+        rule: Option<(Page, Page)>,
+    },
+    Remove {
+        page: Page,
+        rule: Option<(Page, Page)>,
+    },
+}
+
+impl Rules {
+    // The edges between the pages, by index.
+    fn successors(&self, pages: &[Page]) -> Vec<Vec<usize>> {
+        let indices: HashMap<Page, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, &page)| (page, index))
+            .collect();
+
+        pages
+            .iter()
+            .map(|page| {
+                self.after
+                    .get(page)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|later| indices.get(later).copied())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Leaves in place as many pages as can stay, and moves or removes the rest, so the
+    /// fewest edits. Pages stay if no two of them break a rule, for removals, or if no two
+    /// are in the wrong order by way of any chain of rules, for moves. Moves are listed in
+    /// the order they apply.
+    ///
+    /// Fast if the rules order every pair of pages in the update, as they do in the puzzle
+    /// input. Otherwise exponential in the worst case.
+    pub fn repair(&self, pages: &[Page], fix: Fix) -> Result<Vec<Edit>, Cycle> {
+        let successors = self.successors(pages);
+        let order = sort(pages, &successors)?;
+        let total = (0..pages.len()).all(|a| {
+            (a + 1..pages.len()).all(|b| successors[a].contains(&b) || successors[b].contains(&a))
+        });
+
+        let kept_indices = if total {
+            // Then only the order of the kept pages matters.
+            let mut ranks = vec![0; pages.len()];
+            for (rank, &index) in order.iter().enumerate() {
+                ranks[index] = rank;
+            }
+            longest_increasing(&ranks)
+        } else {
+            // Pages later in the update that must come before, directly or not.
+            let earlier: Vec<Vec<usize>> = (0..pages.len())
+                .map(|index| match fix {
+                    Fix::Move => reachable(&successors, index),
+                    Fix::Remove => successors[index].clone(),
+                })
+                .collect();
+            let conflicts: Vec<Vec<usize>> = (0..pages.len())
+                .map(|a| {
+                    (0..pages.len())
+                        .filter(|&b| {
+                            (b > a && earlier[b].contains(&a)) || (b < a && earlier[a].contains(&b))
+                        })
+                        .collect()
+                })
+                .collect();
+            largest_independent(&conflicts)
+        };
+        let kept: HashSet<Page> = kept_indices.iter().map(|&index| pages[index]).collect();
+
+        // Where moved pages go: The kept pages stay in the order they have, which the rules
+        // allow by the choice of pages. Any order will do if some pages are unordered.
+        let order: Vec<Page> = if total || fix == Fix::Remove {
+            order.into_iter().map(|index| pages[index]).collect()
+        } else {
+            let mut successors = successors;
+            for pair in kept_indices.windows(2) {
+                successors[pair[0]].push(pair[1]);
+            }
+            sort(pages, &successors)?
+                .into_iter()
+                .map(|index| pages[index])
+                .collect()
+        };
+
+        // A rule the page breaks with one that stays, or failing that with any other page.
+        let broken = |page: Page| {
+            let position = pages.iter().position(|&other| other == page).unwrap();
+            let broken: Vec<(Page, (Page, Page))> = pages
+                .iter()
+                .enumerate()
+                .filter_map(|(index, &other)| {
+                    let rule = if index < position {
+                        (page, other)
+                    } else {
+                        (other, page)
+                    };
+                    self.must_precede(rule.0, rule.1).then_some((other, rule))
+                })
+                .collect();
+
+            broken
+                .iter()
+                .find(|(other, _)| kept.contains(other))
+                .or_else(|| broken.first())
+                .map(|&(_, rule)| rule)
+        };
+
+        let edits = match fix {
+            Fix::Move => order
+                .iter()
+                .enumerate()
+                .filter(|(_, page)| !kept.contains(page))
+                .map(|(rank, &page)| Edit::Move {
+                    page,
+                    after: rank.checked_sub(1).map(|previous| order[previous]),
+                    rule: broken(page),
+                })
+                .collect(),
+            Fix::Remove => pages
+                .iter()
+                .filter(|page| !kept.contains(page))
+                .map(|&page| Edit::Remove {
+                    page,
+                    rule: broken(page),
+                })
+                .collect(),
+        };
+
+        Ok(edits)
+    }
+}
+
+// Every index that can be reached from `start`, not counting `start` itself.
+fn reachable(successors: &[Vec<usize>], start: usize) -> Vec<usize> {
+    let mut seen = vec![false; successors.len()];
+    let mut stack = vec![start];
+    while let Some(index) = stack.pop() {
+        for &successor in &successors[index] {
+            if !seen[successor] {
+                seen[successor] = true;
+                stack.push(successor);
+            }
+        }
+    }
+
+    (0..successors.len()).filter(|&index| seen[index]).collect()
+}
+
+// A largest set of indices without a conflict between any two, in ascending order. Takes
+// indices with at most one conflict outright, since that never hurts, and otherwise tries
+// with and without the index with the most conflicts.
+fn largest_independent(conflicts: &[Vec<usize>]) -> Vec<usize> {
+    fn search(
+        conflicts: &[Vec<usize>],
+        alive: &mut [bool],
+        chosen: &mut Vec<usize>,
+        best: &mut Vec<usize>,
+    ) {
+        let remaining: Vec<usize> = (0..alive.len()).filter(|&index| alive[index]).collect();
+        if chosen.len() + remaining.len() <= best.len() {
+            return;
+        }
+
+        let degree = |index: usize| {
+            conflicts[index]
+                .iter()
+                .filter(|&&other| alive[other])
+                .count()
+        };
+        let Some(&fewest) = remaining.iter().min_by_key(|&&index| degree(index)) else {
+            best.clone_from(chosen);
+            return;
+        };
+
+        let take =
+            |index: usize, alive: &mut [bool], chosen: &mut Vec<usize>, best: &mut Vec<usize>| {
+                let dropped: Vec<usize> = conflicts[index]
+                    .iter()
+                    .copied()
+                    .filter(|&other| alive[other])
+                    .chain([index])
+                    .collect();
+                for &other in &dropped {
+                    alive[other] = false;
+                }
+                chosen.push(index);
+                search(conflicts, alive, chosen, best);
+                chosen.pop();
+                for &other in &dropped {
+                    alive[other] = true;
+                }
+            };
+
+        if degree(fewest) <= 1 {
+            take(fewest, alive, chosen, best);
+        } else {
+            let most = *remaining
+                .iter()
+                .max_by_key(|&&index| degree(index))
+                .unwrap();
+            take(most, alive, chosen, best);
+            alive[most] = false;
+            search(conflicts, alive, chosen, best);
+            alive[most] = true;
+        }
+    }
+
+    let mut best = Vec::new();
+    search(
+        conflicts,
+        &mut vec![true; conflicts.len()],
+        &mut Vec::new(),
+        &mut best,
+    );
+    best.sort_unstable();
+    best
+}
+
+// Indices of a longest strictly increasing subsequence, in O(n log n).
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // `tails[k]` is the index of the smallest value that ends an increasing run of `k + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+
+    for (index, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+        previous[index] = length.checked_sub(1).map(|length| tails[length]);
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut run = Vec::with_capacity(tails.len());
+    let mut index = tails.last().copied();
+    while let Some(current) = index {
+        run.push(current);
+        index = previous[current];
+    }
+    run.reverse();
+
+    run
+}
+
+// Kahn's algorithm, by index. Ties go to the lowest index.
+fn sort(pages: &[Page], successors: &[Vec<usize>]) -> Result<Vec<usize>, Cycle> {
+    let mut in_degrees = vec![0; pages.len()];
+    for &successor in successors.iter().flatten() {
+        in_degrees[successor] += 1;
+    }
+
+    let mut ready: BTreeSet<usize> = (0..pages.len())
+        .filter(|&index| in_degrees[index] == 0)
+        .collect();
+    let mut sorted = Vec::with_capacity(pages.len());
+    while let Some(index) = ready.pop_first() {
+        sorted.push(index);
+        for &successor in &successors[index] {
+            in_degrees[successor] -= 1;
+            if in_degrees[successor] == 0 {
+                ready.insert(successor);
+            }
+        }
+    }
+
+    if sorted.len() < pages.len() {
+        return Err(cycle(pages, successors, &in_degrees));
+    }
+
+    Ok(sorted)
+}
+
+// Every page that could not be sorted has a predecessor that could not be sorted either,
+// so walking back from one of them must run into a cycle.
+fn cycle(pages: &[Page], successors: &[Vec<usize>], in_degrees: &[usize]) -> Cycle {
+    let predecessor = |index: usize| {
+        (0..pages.len())
+            .find(|&other| in_degrees[other] > 0 && successors[other].contains(&index))
+            .unwrap()
+    };
+
+    let mut walk = vec![in_degrees.iter().position(|&degree| degree > 0).unwrap()];
+    loop {
+        let previous = predecessor(*walk.last().unwrap());
+        if let Some(start) = walk.iter().position(|&index| index == previous) {
+            return Cycle(
+                walk[start..]
+                    .iter()
+                    .rev()
+                    .map(|&index| pages[index])
+                    .collect(),
+            );
+        }
+        walk.push(previous);
+    }
+}
+
+// Checks every pair, not just neighbours, since the rules need not be transitive.
+fn is_ordered(rules: &Rules, pages: &[Page]) -> bool {
+    pages.iter().enumerate().all(|(index, &a)| {
+        pages[index + 1..]
+            .iter()
+            .all(|&b| !rules.must_precede(b, a))
+    })
+}
+
+pub fn part1((pairs, updates): &Input) -> usize {
+    let rules = Rules::new(pairs);
 
     updates
         .iter()
-        .filter(|pages| pages.is_sorted_by(|&a, &b| !pairs.contains(&(b, a))))
+        .filter(|pages| is_ordered(&rules, pages))
         .map(|pages| pages[pages.len() / 2])
         .sum()
 }
 
-pub fn part2(input: &str) -> usize {
-    let (pairs, mut updates) = parse(input);
+/// Updates whose rules form a cycle cannot be sorted, so they are left out. See `cycles`.
+pub fn part2((pairs, updates): &Input) -> usize {
+    let rules = Rules::new(pairs);
 
     updates
-        .iter_mut()
-        .filter(|pages| !pages.is_sorted_by(|&a, &b| !pairs.contains(&(b, a))))
-        .map(|pages| {
-            pages.sort_by(|&a, &b| if pairs.contains(&(a, b)) { Less } else { Equal });
-            pages[pages.len() / 2]
-        })
+        .iter()
+        .filter(|pages| !is_ordered(&rules, pages))
+        .filter_map(|pages| rules.order(pages).ok())
+        .map(|order| order.pages[order.pages.len() / 2])
         .sum()
 }
 
-fn parse(input: &str) -> (HashSet<(usize, usize)>, Vec<Vec<usize>>) {
+/// The updates that cannot be sorted, by index, and the cycle in their rules.
+pub fn cycles((pairs, updates): &Input) -> Vec<(usize, Cycle)> {
+    let rules = Rules::new(pairs);
+
+    updates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, pages)| Some((index, rules.order(pages).err()?)))
+        .collect()
+}
+
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
     let mut lines = input.lines();
 
-    let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+    let mut pairs = Pairs::new();
     loop {
-        let line = lines.next().unwrap();
+        let line = source.next(&mut lines, input, "a blank line")?;
         if line.is_empty() {
             break;
         }
 
-        let (l, r) = line.split_once('|').unwrap();
-        pairs.insert((l.parse::<usize>().unwrap(), r.parse::<usize>().unwrap()));
+        let (l, r) = source.split_once(line, "|")?;
+        pairs.insert((source.number(l)?, source.number(r)?));
     }
 
     let updates = lines
-        .map(|line| line.split(',').map(|page| page.parse().unwrap()).collect())
-        .collect();
+        .map(|line| line.split(',').map(|page| source.number(page)).collect())
+        .collect::<Result<_, _>>()?;
+
+    Ok((pairs, updates))
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Params = ();
+    type Input<'a> = Input;
 
-    (pairs, updates)
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(143, part1(include_str!("example.txt")));
-    assert_eq!(5588, part1(include_str!("input.txt")));
+    assert_eq!(
+        143,
+        part1(&parse(include_str!("../../inputs/day05/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(5);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(123, part2(include_str!("example.txt")));
-    assert_eq!(5331, part2(include_str!("input.txt")));
+    assert_eq!(
+        123,
+        part2(&parse(include_str!("../../inputs/day05/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(5);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("47|53\n97-13\n\n75,47").unwrap_err();
+    assert_eq!("2:6: expected `|`, found nothing", error.to_string());
+    let error = parse("47|53\n\n75,47,x").unwrap_err();
+    assert_eq!("3:7: expected a number, found `x`", error.to_string());
+    let error = parse("47|53\n").unwrap_err();
+    assert_eq!(
+        "2:1: expected a blank line, found nothing",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_order() {
+    let (pairs, _) = parse(include_str!("../../inputs/day05/example.txt")).unwrap();
+    let rules = Rules::new(&pairs);
+    let order = rules.order(&[97, 13, 75, 29, 47]).unwrap();
+    assert_eq!(vec![97, 75, 47, 29, 13], order.pages);
+    assert!(order.ambiguities.is_empty());
+
+    let (pairs, _) = parse("1|2\n1|3\n3|4\n\n").unwrap();
+    let order = Rules::new(&pairs).order(&[4, 2, 3, 1]).unwrap();
+    assert_eq!(vec![1, 2, 3, 4], order.pages);
+    // 1, 3, 2, 4 would do just as well.
+    assert_eq!(vec![(2, 3)], order.ambiguities);
+
+    let (pairs, _) = parse("1|2\n2|3\n3|1\n3|4\n\n").unwrap();
+    let cycle = Rules::new(&pairs).order(&[4, 3, 2, 1]).unwrap_err();
+    assert_eq!("rules form a cycle: 1|2 2|3 3|1", cycle.to_string());
+
+    // The first update is left out, rather than stopping everything.
+    let input = parse("1|2\n2|3\n3|1\n3|4\n\n4,3,2,1\n4,3\n").unwrap();
+    assert_eq!(4, part2(&input));
+    let cycles = cycles(&input);
+    assert_eq!(1, cycles.len());
+    assert_eq!(0, cycles[0].0);
+    assert_eq!("rules form a cycle: 1|2 2|3 3|1", cycles[0].1.to_string());
+}
+
+#[test]
+fn test_repair() {
+    let (pairs, updates) = parse(include_str!("../../inputs/day05/example.txt")).unwrap();
+    let rules = Rules::new(&pairs);
+    assert_eq!(Ok(vec![]), rules.repair(&updates[0], Fix::Move));
+
+    // Either 75 or 97 has to go, moving 97 to the front would do too.
+    assert_eq!(
+        Ok(vec![Edit::Move {
+            page: 75,
+            after: Some(97),
+            rule: Some((97, 75)),
+        }]),
+        rules.repair(&[75, 97, 47, 61, 53], Fix::Move)
+    );
+    assert_eq!(
+        Ok(vec![Edit::Remove {
+            page: 75,
+            rule: Some((97, 75)),
+        }]),
+        rules.repair(&[75, 97, 47, 61, 53], Fix::Remove)
+    );
+
+    // Only direct rules count for removals, so 3 and 1 can both stay. When moving, 1
+    // must end up before 3 by way of 2, so only one page can stay.
+    let (pairs, _) = parse("1|2\n2|3\n\n").unwrap();
+    let rules = Rules::new(&pairs);
+    assert_eq!(
+        Ok(vec![Edit::Remove {
+            page: 2,
+            rule: Some((2, 3)),
+        }]),
+        rules.repair(&[3, 2, 1], Fix::Remove)
+    );
+    assert_eq!(2, rules.repair(&[3, 2, 1], Fix::Move).unwrap().len());
+    assert_eq!(
+        Ok(vec![Edit::Move {
+            page: 3,
+            after: Some(2),
+            rule: Some((2, 3)),
+        }]),
+        rules.repair(&[3, 1, 2], Fix::Move)
+    );
+
+    let (pairs, _) = parse(include_str!("../../inputs/day05/example.txt")).unwrap();
+    let rules = Rules::new(&pairs);
+    // Moving 13 to the end is one edit, sorting would move more.
+    let edits = rules.repair(&[13, 97, 75, 47, 29], Fix::Move).unwrap();
+    assert_eq!(
+        vec![Edit::Move {
+            page: 13,
+            after: Some(29),
+            rule: Some((97, 13)),
+        }],
+        edits
+    );
 }
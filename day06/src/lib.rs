@@ -1,116 +1,538 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
+use aoc_grid::{Direction, Grid, Point, Scalar};
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-type Scalar = i16;
-type Direction = (Scalar, Scalar);
-type Position = (Scalar, Scalar);
+type Position = Point;
 type Positions = HashSet<Position>;
-type Grid = HashMap<Position, char>;
+type Input = (Grid<char>, Vec<Guard>);
+type Step = (Position, Direction, Event);
 
-pub fn part1(input: &str) -> usize {
-    let (grid, start) = parse(input);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Guard {
+    pub pos: Position,
+    pub dir: Direction,
+}
 
-    visit(&grid, start).len()
+/// Which way guards turn in front of an obstacle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Turn {
+    #[default]
+    Right,
+    Left,
 }
 
-pub fn part2(input: &str) -> usize {
-    let (grid, start) = parse(input);
+impl Turn {
+    pub const fn apply(self, dir: Direction) -> Direction {
+        match self {
+            Self::Right => dir.turn_right(),
+            Self::Left => dir.turn_left(),
+        }
+    }
+}
 
-    visit(&grid, start)
-        .par_iter()
-        .filter(|&pos| grid[pos] == '.')
-        .filter(|&pos| is_loop(&grid, start, *pos))
-        .count()
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    pub turn: Turn,
+    /// Whether guards treat each other as obstacles. Then they take turns to step, in the
+    /// order they appear on the map.
+    pub blocking: bool,
 }
 
-fn visit(grid: &Grid, start: Position) -> Positions {
-    let mut pos = start;
-    let mut dir = (0, -1);
-    let mut visited = HashSet::from([pos]);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Stepped forward to the position.
+    Move,
+    /// Turned on the spot, to face the direction.
+    Turn,
+    /// Walked off the map from the position.
+    Exit,
+    /// Got back to a position and direction it had after step `start`, so the walk
+    /// repeats every `length` steps from there. Step 0 is the start.
+    Loop { start: usize, length: usize },
+}
 
-    loop {
-        let next_pos = (pos.0 + dir.0, pos.1 + dir.1);
+/// One guard's walk, one step at a time, with an optional extra obstacle. Ends after an
+/// exit or a loop.
+pub struct Patrol<'a> {
+    grid: &'a Grid<char>,
+    turn: Turn,
+    obstacle: Option<Position>,
+    pos: Position,
+    dir: Direction,
+    // When each position and direction was first seen.
+    seen: HashMap<(Position, Direction), usize>,
+    done: bool,
+}
 
-        match grid.get(&next_pos) {
-            Some('#') => {
-                dir = turn_right(dir);
-            }
-            Some(_) => {
-                pos = next_pos;
-                visited.insert(pos);
-            }
-            _ => {
-                return visited;
-            }
+impl<'a> Patrol<'a> {
+    pub fn new(grid: &'a Grid<char>, guard: Guard, turn: Turn, obstacle: Option<Position>) -> Self {
+        Self {
+            grid,
+            turn,
+            obstacle,
+            pos: guard.pos,
+            dir: guard.dir,
+            seen: HashMap::from([((guard.pos, guard.dir), 0)]),
+            done: false,
         }
     }
 }
 
-fn is_loop(grid: &Grid, start: Position, obstacle: Position) -> bool {
-    let mut pos = start;
-    let mut dir = (0, -1);
-    let mut seen = HashSet::from([(pos, dir)]);
+impl Iterator for Patrol<'_> {
+    type Item = Step;
 
-    loop {
-        let next_pos = (pos.0 + dir.0, pos.1 + dir.1);
-        let next_char = if next_pos == obstacle {
-            Some(&'#')
+    fn next(&mut self) -> Option<Step> {
+        if self.done {
+            return None;
+        }
+
+        let next_pos = self.pos + self.dir;
+        let event = if Some(next_pos) == self.obstacle {
+            Event::Turn
         } else {
-            grid.get(&next_pos)
+            match self.grid.get(next_pos) {
+                Some('#') => Event::Turn,
+                Some(_) => Event::Move,
+                None => Event::Exit,
+            }
         };
 
-        match next_char {
-            Some('#') => {
-                dir = turn_right(dir);
-                if !seen.insert((pos, dir)) {
-                    return true;
-                }
+        match event {
+            Event::Turn => self.dir = self.turn.apply(self.dir),
+            Event::Move => self.pos = next_pos,
+            Event::Exit | Event::Loop { .. } => {
+                self.done = true;
+                return Some((self.pos, self.dir, event));
             }
-            Some(_) => {
-                pos = next_pos;
+        }
+
+        let step = self.seen.len();
+        if let Some(&start) = self.seen.get(&(self.pos, self.dir)) {
+            self.done = true;
+            let length = step - start;
+            return Some((self.pos, self.dir, Event::Loop { start, length }));
+        }
+        self.seen.insert((self.pos, self.dir), step);
+
+        Some((self.pos, self.dir, event))
+    }
+}
+
+/// Draws the path like the puzzle does: `|` and `-` for moves, `+` for turns and
+/// crossings, and `O` for the extra obstacle.
+pub fn render(
+    grid: &Grid<char>,
+    obstacle: Option<Position>,
+    steps: impl IntoIterator<Item = Step>,
+) -> Grid<char> {
+    let mut rendered = grid.clone();
+    let mut draw = |pos, c| {
+        let cell = &mut rendered[pos];
+        *cell = match (*cell, c) {
+            ('.', _) => c,
+            ('|', '-') | ('-', '|') | ('|' | '-', '+') => '+',
+            (cell, _) => cell,
+        };
+    };
+
+    for (pos, dir, event) in steps {
+        match event {
+            Event::Move if matches!(dir, Direction::Up | Direction::Down) => draw(pos, '|'),
+            Event::Move => draw(pos, '-'),
+            Event::Turn => draw(pos, '+'),
+            Event::Exit | Event::Loop { .. } => (),
+        }
+    }
+    if let Some(obstacle) = obstacle {
+        rendered[obstacle] = 'O';
+    }
+
+    rendered
+}
+
+/// Positions visited by any guard.
+pub fn part1((grid, guards): &Input, params: &Params) -> usize {
+    if params.blocking {
+        return walk_together(grid, guards, params.turn, None).0.len();
+    }
+
+    guards
+        .iter()
+        .flat_map(|&guard| path(grid, guard, params.turn).0.into_keys())
+        .collect::<Positions>()
+        .len()
+}
+
+/// Positions where a single extra obstacle makes any guard walk in a loop.
+pub fn part2((grid, guards): &Input, params: &Params) -> usize {
+    // An obstacle that no guard runs into changes nothing.
+    let free = |pos: &Position| grid[*pos] == '.';
+    let candidates = |visited: Positions, looped: bool| -> Vec<Position> {
+        if looped {
+            grid.points().filter(free).collect()
+        } else {
+            visited.into_iter().filter(free).collect()
+        }
+    };
+
+    if params.blocking {
+        let (visited, looped) = walk_together(grid, guards, params.turn, None);
+        return candidates(visited, looped)
+            .par_iter()
+            .filter(|&&obstacle| walk_together(grid, guards, params.turn, Some(obstacle)).1)
+            .count();
+    }
+
+    let jumps = Jumps::from_grid(grid);
+    let paths: Vec<_> = guards
+        .iter()
+        .map(|&guard| path(grid, guard, params.turn))
+        .collect();
+    let visited = paths
+        .iter()
+        .flat_map(|(path, _)| path.keys().copied())
+        .collect();
+    let looped = paths.iter().any(|&(_, looped)| looped);
+
+    candidates(visited, looped)
+        .par_iter()
+        .filter(|&&obstacle| {
+            paths
+                .iter()
+                .any(|(path, looped)| match path.get(&obstacle) {
+                    Some(&before) => is_loop(&jumps, before, params.turn, obstacle),
+                    None => *looped,
+                })
+        })
+        .count()
+}
+
+// Each position on the guard's path, with where the guard was just before first stepping
+// onto it, since an obstacle there changes nothing before that. Also whether it loops.
+fn path(grid: &Grid<char>, guard: Guard, turn: Turn) -> (HashMap<Position, Guard>, bool) {
+    let mut path = HashMap::from([(guard.pos, guard)]);
+    let mut previous = guard;
+    let mut looped = false;
+
+    for (pos, dir, event) in Patrol::new(grid, guard, turn, None) {
+        path.entry(pos).or_insert(previous);
+        previous = Guard { pos, dir };
+        looped = matches!(event, Event::Loop { .. });
+    }
+
+    (path, looped)
+}
+
+// Guards take turns to step, each treating the others as obstacles. Returns the positions
+// visited by any of them, and whether those left on the map walk in a loop.
+fn walk_together(
+    grid: &Grid<char>,
+    guards: &[Guard],
+    turn: Turn,
+    obstacle: Option<Position>,
+) -> (Positions, bool) {
+    let mut guards: Vec<Option<Guard>> = guards.iter().copied().map(Some).collect();
+    let mut visited: Positions = guards.iter().flatten().map(|guard| guard.pos).collect();
+    let mut seen = HashSet::from([guards.clone()]);
+
+    loop {
+        for index in 0..guards.len() {
+            let Some(guard) = guards[index] else {
+                continue;
+            };
+            let next_pos = guard.pos + guard.dir;
+            let blocked = Some(next_pos) == obstacle
+                || grid.get(next_pos) == Some(&'#')
+                || guards.iter().flatten().any(|other| other.pos == next_pos);
+
+            guards[index] = if blocked {
+                Some(Guard {
+                    dir: turn.apply(guard.dir),
+                    ..guard
+                })
+            } else if grid.contains(next_pos) {
+                visited.insert(next_pos);
+                Some(Guard {
+                    pos: next_pos,
+                    ..guard
+                })
+            } else {
+                None
+            };
+        }
+
+        if guards.iter().all(Option::is_none) {
+            return (visited, false);
+        }
+        if !seen.insert(guards.clone()) {
+            return (visited, true);
+        }
+    }
+}
+
+/// The obstacles in each row and each column, sorted.
+///
+/// The guard can go from one obstacle to the next without walking. This takes memory
+/// for the obstacles only, not for every cell, and an extra obstacle only matters in its
+/// own row and column.
+pub struct Jumps {
+    rows: Vec<Vec<Scalar>>,
+    columns: Vec<Vec<Scalar>>,
+}
+
+impl Jumps {
+    pub fn new(width: usize, height: usize, obstacles: impl IntoIterator<Item = Position>) -> Self {
+        let mut rows = vec![Vec::new(); height];
+        let mut columns = vec![Vec::new(); width];
+        for obstacle in obstacles {
+            rows[obstacle.y.cast_unsigned()].push(obstacle.x);
+            columns[obstacle.x.cast_unsigned()].push(obstacle.y);
+        }
+        for line in rows.iter_mut().chain(&mut columns) {
+            line.sort_unstable();
+        }
+
+        Self { rows, columns }
+    }
+
+    pub fn from_grid(grid: &Grid<char>) -> Self {
+        let obstacles = grid.iter().filter(|&(_, &c)| c == '#').map(|(pos, _)| pos);
+
+        Self::new(grid.width(), grid.height(), obstacles)
+    }
+
+    /// Where the guard stops in front of the next obstacle, or `None` if it walks off
+    /// the map.
+    pub fn jump(&self, pos: Position, dir: Direction, extra: Option<Position>) -> Option<Position> {
+        let column = &self.columns[pos.x.cast_unsigned()];
+        let row = &self.rows[pos.y.cast_unsigned()];
+        let in_column = extra.filter(|extra| extra.x == pos.x).map(|extra| extra.y);
+        let in_row = extra.filter(|extra| extra.y == pos.y).map(|extra| extra.x);
+
+        match dir {
+            Direction::Up => {
+                nearest(column, pos.y, in_column, false).map(|y| Point::new(pos.x, y + 1))
             }
-            _ => {
-                return false;
+            Direction::Down => {
+                nearest(column, pos.y, in_column, true).map(|y| Point::new(pos.x, y - 1))
             }
+            Direction::Left => nearest(row, pos.x, in_row, false).map(|x| Point::new(x + 1, pos.y)),
+            Direction::Right => nearest(row, pos.x, in_row, true).map(|x| Point::new(x - 1, pos.y)),
+            _ => panic!("the guard does not move diagonally"),
         }
     }
 }
 
-fn turn_right(direction: Direction) -> Direction {
-    match direction {
-        (0, -1) => (1, 0),
-        (1, 0) => (0, 1),
-        (0, 1) => (-1, 0),
-        (-1, 0) => (0, -1),
-        _ => unreachable!(),
+// The nearest obstacle after or before `at` on a sorted line.
+fn nearest(line: &[Scalar], at: Scalar, extra: Option<Scalar>, forward: bool) -> Option<Scalar> {
+    let split = line.partition_point(|&other| other < at);
+    if forward {
+        [line.get(split).copied(), extra.filter(|&extra| extra > at)]
+            .into_iter()
+            .flatten()
+            .min()
+    } else {
+        [
+            split.checked_sub(1).map(|index| line[index]),
+            extra.filter(|&extra| extra < at),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
     }
 }
 
-fn parse(input: &str) -> (Grid, Position) {
-    let mut grid = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            grid.insert((x as Scalar, y as Scalar), c);
+/// Whether the guard walks in a loop once `obstacle` is added, ignoring other guards.
+/// Takes time in proportion to the number of turns, not steps.
+pub fn is_loop(jumps: &Jumps, guard: Guard, turn: Turn, obstacle: Position) -> bool {
+    let Guard { mut pos, mut dir } = guard;
+    let mut seen = HashSet::new();
+
+    while let Some(stop) = jumps.jump(pos, dir, Some(obstacle)) {
+        pos = stop;
+        dir = turn.apply(dir);
+        if !seen.insert((pos, dir)) {
+            return true;
         }
     }
 
-    let start = grid
+    false
+}
+
+/// Guards are `^`, `>`, `v` or `<`, facing that way.
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let grid = Grid::parse(input)?;
+    let guards: Vec<_> = grid
         .iter()
-        .find_map(|(&pos, &c)| (c == '^').then_some(pos))
-        .unwrap();
+        .filter_map(|(pos, &c)| {
+            let dir = match c {
+                '^' => Direction::Up,
+                '>' => Direction::Right,
+                'v' => Direction::Down,
+                '<' => Direction::Left,
+                _ => return None,
+            };
+            Some(Guard { pos, dir })
+        })
+        .collect();
+
+    if guards.is_empty() {
+        return Err(Source::new(input).missing(input, "a guard"));
+    }
 
-    (grid, start)
+    Ok((grid, guards))
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Params = Params;
+    type Input<'a> = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, params: &Params) -> Answer {
+        part1(input, params).into()
+    }
+
+    fn part2(input: &Input, params: &Params) -> Answer {
+        part2(input, params).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(41, part1(include_str!("example.txt")));
-    assert_eq!(5030, part1(include_str!("input.txt")));
+    assert_eq!(
+        41,
+        part1(
+            &parse(include_str!("../../inputs/day06/example.txt")).unwrap(),
+            &Params::default()
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(6);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap(), &Params::default()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(6, part2(include_str!("example.txt")));
-    assert_eq!(1928, part2(include_str!("input.txt")));
+    assert_eq!(
+        6,
+        part2(
+            &parse(include_str!("../../inputs/day06/example.txt")).unwrap(),
+            &Params::default()
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(6);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap(), &Params::default()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("..#\n...\n").unwrap_err();
+    assert_eq!("3:1: expected a guard, found nothing", error.to_string());
+}
+
+#[test]
+fn test_patrol() {
+    let (grid, guards) = parse(include_str!("../../inputs/day06/example.txt")).unwrap();
+    let guard = guards[0];
+    let obstacle = Point::new(3, 6);
+    let steps: Vec<_> = Patrol::new(&grid, guard, Turn::Right, Some(obstacle)).collect();
+    assert_eq!(
+        Some(&(
+            guard.pos,
+            Direction::Up,
+            Event::Loop {
+                start: 0,
+                length: 22
+            }
+        )),
+        steps.last()
+    );
+    assert_eq!(
+        "....#.....\n\
+         ....+---+#\n\
+         ....|...|.\n\
+         ..#.|...|.\n\
+         ....|..#|.\n\
+         ....|...|.\n\
+         .#.O^---+.\n\
+         ........#.\n\
+         #.........\n\
+         ......#...\n",
+        render(&grid, Some(obstacle), steps).to_string()
+    );
+
+    let steps: Vec<_> = Patrol::new(&grid, guard, Turn::Right, None).collect();
+    assert_eq!(
+        Some(&(Point::new(7, 9), Direction::Down, Event::Exit)),
+        steps.last()
+    );
+}
+
+#[test]
+fn test_jumps() {
+    // A guard going round the edge of a huge map, far too big for a grid of cells.
+    let size: Scalar = 10_000;
+    let obstacles = [(1, 0), (size - 1, 1), (size - 2, size - 1), (0, size - 2)];
+    let jumps = Jumps::new(
+        size.cast_unsigned(),
+        size.cast_unsigned(),
+        obstacles.map(|(x, y)| Point::new(x, y)),
+    );
+    let guard = Guard {
+        pos: Point::new(1, size - 2),
+        dir: Direction::Up,
+    };
+    assert_eq!(
+        Some(Point::new(1, 1)),
+        jumps.jump(guard.pos, guard.dir, None)
+    );
+    assert!(is_loop(&jumps, guard, Turn::Right, Point::new(5, 5)));
+
+    // An obstacle in the top row sends the guard down and off the map.
+    let obstacle = Point::new(size / 2, 1);
+    assert_eq!(
+        Some(Point::new(size / 2 - 1, 1)),
+        jumps.jump(Point::new(1, 1), Direction::Right, Some(obstacle))
+    );
+    assert!(!is_loop(&jumps, guard, Turn::Right, obstacle));
+}
+
+#[test]
+fn test_guards() {
+    // Mirrored left to right, a guard turning left walks the same way.
+    let example = include_str!("../../inputs/day06/example.txt");
+    let mirrored: String = example
+        .lines()
+        .map(|line| line.chars().rev().chain(['\n']).collect::<String>())
+        .collect();
+    let left = Params {
+        turn: Turn::Left,
+        blocking: false,
+    };
+    assert_eq!(41, part1(&parse(&mirrored).unwrap(), &left));
+    assert_eq!(6, part2(&parse(&mirrored).unwrap(), &left));
+
+    // Two guards walking towards each other in a corridor.
+    let input = parse("....\n>..<\n....\n").unwrap();
+    let blocking = Params {
+        turn: Turn::Right,
+        blocking: true,
+    };
+    assert_eq!(4, part1(&input, &Params::default()));
+    assert_eq!(6, part1(&input, &blocking));
+
+    // A single guard cannot get in its own way.
+    let example = parse(example).unwrap();
+    assert_eq!(6, part2(&example, &blocking));
 }
@@ -1,54 +1,503 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use rayon::prelude::*;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+type Equation = (usize, Vec<usize>);
+
+/// The values `left` could have had, given `left op right == target`.
+pub enum Lefts {
+    None,
+    One(usize),
+    Range(RangeInclusive<usize>),
+    /// Any value at all, like for `left * 0 == 0`.
+    Any,
+    /// Too big for `usize`.
+    Overflow,
+}
+
+/// Why an operator gave no result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// Like a negative difference, or division by zero.
+    Undefined,
+    /// Too big for `usize`.
+    Overflow,
+}
+
+/// An operator, applied left to right with no precedence.
+pub trait Operator: fmt::Display + Sync {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure>;
+
+    fn undo(&self, target: usize, right: usize) -> Lefts;
+}
+
+pub struct Add;
+pub struct Mul;
+/// Only defined if the result is not negative.
+pub struct Sub;
+/// Rounds down. Not defined for division by zero.
+pub struct Div;
+pub struct Xor;
+/// Appends the digits of `right` in the given base. Zero has no digits, so `x || 0` is `x`.
+pub struct Concat(usize);
+
+impl Operator for Add {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        left.checked_add(right).ok_or(Failure::Overflow)
+    }
+
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        target.checked_sub(right).map_or(Lefts::None, Lefts::One)
+    }
+}
+
+impl Operator for Mul {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        left.checked_mul(right).ok_or(Failure::Overflow)
+    }
+
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        match (target, right) {
+            (0, 0) => Lefts::Any,
+            (_, 0) => Lefts::None,
+            _ if target.is_multiple_of(right) => Lefts::One(target / right),
+            _ => Lefts::None,
+        }
+    }
+}
+
+impl Operator for Sub {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        left.checked_sub(right).ok_or(Failure::Undefined)
+    }
+
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        target
+            .checked_add(right)
+            .map_or(Lefts::Overflow, Lefts::One)
+    }
+}
+
+impl Operator for Div {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        left.checked_div(right).ok_or(Failure::Undefined)
+    }
+
+    // All of `target * right ..= target * right + right - 1` round down to `target`, as
+    // far as they fit in `usize`.
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        match (right, target.checked_mul(right)) {
+            (0, _) => Lefts::None,
+            (1, _) => Lefts::One(target),
+            (_, None) => Lefts::Overflow,
+            (_, Some(start)) => Lefts::Range(start..=start.saturating_add(right - 1)),
+        }
+    }
+}
+
+impl Operator for Xor {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        Ok(left ^ right)
+    }
+
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        Lefts::One(target ^ right)
+    }
+}
+
+impl Concat {
+    /// Panics if `base` is less than 2, which has no digits to append.
+    pub const fn new(base: usize) -> Self {
+        assert!(base >= 2, "concatenation needs a base of at least 2");
+        Self(base)
+    }
+
+    // What `left` is multiplied by before `right` is added.
+    fn shift(&self, right: usize) -> Option<usize> {
+        let mut power: usize = 1;
+        while power <= right {
+            power = power.checked_mul(self.0)?;
+        }
+
+        Some(power)
+    }
+}
+
+impl Operator for Concat {
+    fn apply(&self, left: usize, right: usize) -> Result<usize, Failure> {
+        self.shift(right)
+            .and_then(|power| left.checked_mul(power)?.checked_add(right))
+            .ok_or(Failure::Overflow)
+    }
+
+    fn undo(&self, target: usize, right: usize) -> Lefts {
+        match self.shift(right) {
+            None => Lefts::Overflow,
+            Some(power) if target % power == right => Lefts::One(target / power),
+            Some(_) => Lefts::None,
+        }
+    }
+}
+
+impl fmt::Display for Add {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "+")
+    }
+}
+
+impl fmt::Display for Mul {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "*")
+    }
+}
+
+impl fmt::Display for Sub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "-")
+    }
+}
+
+impl fmt::Display for Div {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/")
+    }
+}
+
+impl fmt::Display for Xor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "^")
+    }
+}
+
+impl fmt::Display for Concat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            10 => write!(f, "||"),
+            base => write!(f, "||({base})"),
+        }
+    }
+}
+
+pub const PART1: [&dyn Operator; 2] = [&Add, &Mul];
+pub const PART2: [&dyn Operator; 3] = [&Add, &Mul, &Concat::new(10)];
+
+/// Parts with operators between them, like `81 + 40 * 27`.
+pub struct Expression<'a> {
+    pub parts: &'a [usize],
+    pub operators: Vec<&'a dyn Operator>,
+}
+
+impl Expression<'_> {
+    /// `None` if some step does not fit in `usize` or is not defined.
+    pub fn evaluate(&self) -> Option<usize> {
+        self.parts[1..]
+            .iter()
+            .zip(&self.operators)
+            .try_fold(self.parts[0], |left, (&right, op)| {
+                op.apply(left, right).ok()
+            })
+    }
+}
+
+impl fmt::Display for Expression<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.parts[0])?;
+        for (part, op) in self.parts[1..].iter().zip(&self.operators) {
+            write!(f, " {op} {part}")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub enum Outcome<'a> {
+    Solved(Expression<'a>),
+    Unsolvable,
+    /// No solution found, but some would need numbers too big for `usize`.
+    Overflow,
+}
+
+pub fn check<'a>(target: usize, parts: &'a [usize], operators: &[&'a dyn Operator]) -> Outcome<'a> {
+    let mut overflow = false;
+
+    match search(target, parts, operators, &mut overflow) {
+        Some(indices) => Outcome::Solved(Expression {
+            parts,
+            operators: indices.into_iter().map(|index| operators[index]).collect(),
+        }),
+        None if overflow => Outcome::Overflow,
+        None => Outcome::Unsolvable,
+    }
+}
+
+// Works right to left, undoing the last operation on the target, so only values that
+// could have produced the target are tried. Where many could have, like for `/`, it works
+// forwards through the remaining parts instead, so the search does not grow with the
+// values. Returns indices into `operators`.
+fn search(
+    target: usize,
+    parts: &[usize],
+    operators: &[&dyn Operator],
+    overflow: &mut bool,
+) -> Option<Vec<usize>> {
+    let (&last, rest) = parts.split_last()?;
+    if rest.is_empty() {
+        return (target == last).then(Vec::new);
+    }
+
+    for (index, op) in operators.iter().enumerate() {
+        let found = match op.undo(target, last) {
+            Lefts::None => None,
+            Lefts::One(left) => search(left, rest, operators, overflow),
+            Lefts::Range(lefts) => {
+                forward(rest, operators, overflow, &|left| lefts.contains(&left))
+            }
+            Lefts::Any => forward(rest, operators, overflow, &|_| true),
+            Lefts::Overflow => {
+                *overflow = true;
+                None
+            }
+        };
+
+        if let Some(mut indices) = found {
+            indices.push(index);
+            return Some(indices);
+        }
+    }
+
+    None
+}
+
+// Tries every choice of operators, left to right, until the value of the parts is one
+// that `accept` takes.
+fn forward(
+    parts: &[usize],
+    operators: &[&dyn Operator],
+    overflow: &mut bool,
+    accept: &dyn Fn(usize) -> bool,
+) -> Option<Vec<usize>> {
+    fn go(
+        left: usize,
+        parts: &[usize],
+        operators: &[&dyn Operator],
+        overflow: &mut bool,
+        accept: &dyn Fn(usize) -> bool,
+    ) -> Option<Vec<usize>> {
+        let Some((&right, rest)) = parts.split_first() else {
+            return accept(left).then(Vec::new);
+        };
+
+        for (index, op) in operators.iter().enumerate() {
+            match op.apply(left, right) {
+                Ok(value) => {
+                    if let Some(mut indices) = go(value, rest, operators, overflow, accept) {
+                        indices.insert(0, index);
+                        return Some(indices);
+                    }
+                }
+                Err(Failure::Overflow) => *overflow = true,
+                Err(Failure::Undefined) => (),
+            }
+        }
+
+        None
+    }
+
+    go(parts[0], &parts[1..], operators, overflow, accept)
+}
+
+/// What became of each equation, in order.
+pub fn outcomes<'a>(equations: &'a [Equation], operators: &[&'a dyn Operator]) -> Vec<Outcome<'a>> {
+    equations
+        .par_iter()
+        .map(|(target, parts)| check(*target, parts, operators))
+        .collect()
+}
+
+/// The sum of the targets that can be reached.
+pub fn solve(equations: &[Equation], operators: &[&dyn Operator]) -> usize {
+    equations
+        .iter()
+        .zip(outcomes(equations, operators))
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Solved(_)))
+        .map(|((target, _), _)| target)
+        .sum()
+}
+
+pub fn parse(input: &str) -> Result<Vec<Equation>, ParseError> {
+    let source = Source::new(input);
 
-pub fn solve(input: &str, op_count: usize) -> usize {
     input
-        .par_lines()
-        .filter_map(|line| {
-            let (target, parts) = line.split_once(':').unwrap();
-            let target: usize = target.parse().unwrap();
-            let parts: Vec<usize> = parts
+        .lines()
+        .map(|line| {
+            let (target, parts) = source.split_once(line, ":")?;
+            let target = source.number(target)?;
+            let parts = parts
                 .split_whitespace()
-                .map(|part| part.parse().unwrap())
-                .collect();
-
-            // n ** (m - 1) candidates for n operators and m parts.
-            let candidate_count = op_count.pow((parts.len() - 1) as u32);
-            (0..=candidate_count)
-                .map(|mut candidate| {
-                    let mut result = parts[0];
-                    for &part in parts.iter().skip(1) {
-                        result = match candidate % op_count {
-                            0 => result + part,
-                            1 => result * part,
-                            2 => concat(result, part),
-                            _ => unimplemented!(),
-                        };
-                        candidate /= op_count;
-                    }
-                    result
-                })
-                .find(|&result| result == target)
+                .map(|part| source.number(part))
+                .collect::<Result<Vec<_>, _>>()?;
+            if parts.is_empty() {
+                return Err(source.missing(line, "a number"));
+            }
+
+            Ok((target, parts))
         })
-        .sum()
+        .collect()
 }
 
-const fn concat(mut left: usize, right: usize) -> usize {
-    let mut remainder = right;
-    while remainder != 0 {
-        remainder /= 10;
-        left *= 10;
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Params = ();
+    type Input<'a> = Vec<Equation>;
+
+    fn parse(input: &str) -> Result<Vec<Equation>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(equations: &Vec<Equation>, (): &()) -> Answer {
+        solve(equations, &PART1).into()
+    }
+
+    fn part2(equations: &Vec<Equation>, (): &()) -> Answer {
+        solve(equations, &PART2).into()
     }
-    left + right
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(3749, solve(include_str!("example.txt"), 2));
-    assert_eq!(5512534574980, solve(include_str!("input.txt"), 2));
+    assert_eq!(
+        3749,
+        solve(
+            &parse(include_str!("../../inputs/day07/example.txt")).unwrap(),
+            &PART1
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(7);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), &PART1))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(11387, solve(include_str!("example.txt"), 3));
-    assert_eq!(328790210468594, solve(include_str!("input.txt"), 3));
+    assert_eq!(
+        11387,
+        solve(
+            &parse(include_str!("../../inputs/day07/example.txt")).unwrap(),
+            &PART2
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(7);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), &PART2))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("190: 10 19\n3267 81 40 27\n").unwrap_err();
+    assert_eq!("2:14: expected `:`, found nothing", error.to_string());
+    let error = parse("190:\n").unwrap_err();
+    assert_eq!("1:5: expected a number, found nothing", error.to_string());
+}
+
+#[test]
+fn test_long_equation() {
+    // 40 even parts, added or multiplied, and the same with `||` thrown in. No way to get
+    // an odd number from them.
+    let parts: Vec<usize> = (0..40).map(|i| 2 * (i % 9 + 1)).collect();
+    let mut add_mul = parts[0];
+    let mut with_concat = parts[0];
+    for (i, &part) in parts.iter().enumerate().skip(1) {
+        (add_mul, with_concat) = match i % 7 {
+            0 => (add_mul * part, with_concat * part),
+            3 => (
+                add_mul + part,
+                Concat::new(10).apply(with_concat, part).unwrap(),
+            ),
+            _ => (add_mul + part, with_concat + part),
+        };
+    }
+
+    let solved = |target, operators: &[&dyn Operator]| match check(target, &parts, operators) {
+        Outcome::Solved(expression) => Some(expression.evaluate()),
+        Outcome::Unsolvable | Outcome::Overflow => None,
+    };
+    assert_eq!(Some(Some(add_mul)), solved(add_mul, &PART1));
+    assert_eq!(None, solved(add_mul + 1, &PART1));
+    assert_eq!(Some(Some(with_concat)), solved(with_concat, &PART2));
+    assert_eq!(None, solved(with_concat + 1, &PART2));
+    assert_eq!(
+        add_mul,
+        solve(&[(add_mul, parts.clone()), (add_mul + 1, parts)], &PART2)
+    );
+}
+
+#[test]
+fn test_operators() {
+    let witness = |target, parts: &[usize], operators: &[&dyn Operator]| match check(
+        target, parts, operators,
+    ) {
+        Outcome::Solved(expression) => expression.to_string(),
+        Outcome::Unsolvable => "unsolvable".to_string(),
+        Outcome::Overflow => "overflow".to_string(),
+    };
+    // `81 + 40 * 27` works too. The last operator is tried in order, `+` first.
+    assert_eq!("81 * 40 + 27", witness(3267, &[81, 40, 27], &PART1));
+    assert_eq!("6 * 8 || 6 * 15", witness(7290, &[6, 8, 6, 15], &PART2));
+    assert_eq!("unsolvable", witness(7290, &[6, 8, 6, 15], &PART1));
+
+    let all: [&dyn Operator; 6] = [&Add, &Mul, &Sub, &Div, &Xor, &Concat::new(2)];
+    assert_eq!("10 - 4 / 4", witness(1, &[10, 4, 4], &all));
+    assert_eq!("5 ^ 3", witness(6, &[5, 3], &all));
+    assert_eq!("2 ||(2) 3", witness(11, &[2, 3], &all));
+    assert_eq!("3 * 0", witness(0, &[3, 0], &all));
+
+    // Would need `usize::MAX + 1` before subtracting.
+    let max = usize::MAX;
+    assert_eq!("overflow", witness(max, &[max, 2, 1], &[&Add, &Sub]));
+    assert_eq!("unsolvable", witness(max, &[max, 2, 1], &[&Add]));
+    assert_eq!("overflow", witness(max, &[max, 2, 1], &[&Div]));
+
+    // Lefts and targets right at the top of `usize`.
+    let at_max = |target, parts: &[usize], op: &dyn Operator| {
+        let expected = format!("{} {op} {}", parts[0], parts[1]);
+        assert_eq!(expected, witness(target, parts, &[op]));
+    };
+    at_max(max, &[max, 0], &Add);
+    at_max(max - 2, &[max, 2], &Sub);
+    at_max(max, &[max, 1], &Mul);
+    at_max(max, &[max, 1], &Div);
+    at_max(max / 2, &[max, 2], &Div);
+    at_max(max, &[max, 0], &Xor);
+    at_max(0, &[max, max], &Xor);
+    at_max(max, &[max / 10, max % 10], &Concat::new(10));
+    let equations = parse("18446744073709551615: 18446744073709551615 0\n").unwrap();
+    assert_eq!(max, solve(&equations, &PART1));
+
+    // A hundred million lefts divide by 100000000 to the same value, too many to try one by
+    // one.
+    assert_eq!(
+        "unsolvable",
+        witness(1000, &[3, 100_000_000, 7], &[&Add, &Div])
+    );
+    assert_eq!(
+        "7 * 100000000 * 100000000 / 100000000 / 9",
+        witness(
+            7 * 100_000_000 / 9,
+            &[7, 100_000_000, 100_000_000, 100_000_000, 9],
+            &[&Add, &Mul, &Div]
+        )
+    );
+
+    let equations = parse("7: 3 4\n8: 3 4\n").unwrap();
+    let outcomes = outcomes(&equations, &PART1);
+    assert!(
+        matches!(&outcomes[0], Outcome::Solved(expression) if expression.to_string() == "3 + 4")
+    );
+    assert!(matches!(outcomes[1], Outcome::Unsolvable));
 }
@@ -1,78 +1,338 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
-
-type Scalar = i8;
-type Position = (Scalar, Scalar);
-type Grid = HashMap<Position, char>;
-type Antennas = HashMap<char, Vec<Position>>;
-
-pub fn solve(input: &str) -> usize {
-    let (grid, antennas) = parse(input);
-
-    let mut antinodes = HashSet::new();
-    for (antenna, positions) in antennas {
-        for (x1, y1) in &positions {
-            for (x2, y2) in &positions {
-                let (dx, dy) = (x1 - x2, y1 - y2);
-                let antinode = (x1 + dx, y1 + dy);
-                if let Some(&c) = grid.get(&antinode) {
-                    if c != antenna {
-                        antinodes.insert(antinode);
-                    }
-                }
-            }
+use aoc_core::{Answer, ParseError, Solution};
+use aoc_grid::{Grid, Point, Scalar};
+use itertools::Itertools;
+use std::array;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub type Coordinate = i64;
+/// A position or offset, with one coordinate per dimension.
+pub type Vector<const N: usize> = [Coordinate; N];
+pub type Antennas<const N: usize> = HashMap<char, Vec<Vector<N>>>;
+type Input = (Grid<char>, Antennas<2>);
+
+/// Where two antennas of the same frequency make antinodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// In line with the antennas, `n` times as far from one as from the other, and not
+    /// between them. Part 1 uses 2.
+    Ratio(Coordinate),
+    /// Every point in line with the antennas, including the antennas.
+    Collinear,
+    /// Every point in line with the antennas and strictly between them.
+    Between,
+}
+
+/// Whether `pos` lies in the box from the origin up to, but not including, `size`.
+pub fn contains<const N: usize>(size: &Vector<N>, pos: &Vector<N>) -> bool {
+    pos.iter()
+        .zip(size)
+        .all(|(&c, &size)| (0..size).contains(&c))
+}
+
+const fn gcd(a: Coordinate, b: Coordinate) -> Coordinate {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Like `antinodes_of`, but keeps ratio points outside `size`. Collinear points go on
+// forever, so those stop at the edge.
+fn candidates<const N: usize>(
+    a: Vector<N>,
+    b: Vector<N>,
+    rule: Rule,
+    size: &Vector<N>,
+) -> Vec<Vector<N>> {
+    if a == b {
+        return Vec::new();
+    }
+
+    // The line from `a` to `b` passes through a grid point every `unit`, `steps` times.
+    let steps = (0..N).fold(0, |steps, i| gcd(steps, (b[i] - a[i]).abs()));
+    let unit: Vector<N> = array::from_fn(|i| (b[i] - a[i]) / steps);
+    let along = |count: Coordinate| -> Vector<N> { array::from_fn(|i| a[i] + unit[i] * count) };
+
+    match rule {
+        // `steps / (n - 1)` before `a`, or after `b`.
+        Rule::Ratio(n) if n >= 2 && steps % (n - 1) == 0 => {
+            vec![along(-steps / (n - 1)), along(steps + steps / (n - 1))]
         }
+        Rule::Ratio(_) => Vec::new(),
+        Rule::Collinear => {
+            let before = (1..).map(|count| along(-count));
+            let after = (0..).map(along);
+            before
+                .take_while(|pos| contains(size, pos))
+                .chain(after.take_while(|pos| contains(size, pos)))
+                .collect()
+        }
+        Rule::Between => (1..steps).map(along).collect(),
     }
+}
+
+/// The antinodes of two antennas that lie within `size`.
+pub fn antinodes_of<const N: usize>(
+    a: Vector<N>,
+    b: Vector<N>,
+    rule: Rule,
+    size: &Vector<N>,
+) -> Vec<Vector<N>> {
+    let mut antinodes = candidates(a, b, rule, size);
+    antinodes.retain(|pos| contains(size, pos));
+    antinodes
+}
 
-    antinodes.len()
+/// The distinct antinodes of all antennas that lie within `size`.
+pub fn antinodes<const N: usize>(
+    antennas: &Antennas<N>,
+    rule: Rule,
+    size: &Vector<N>,
+) -> HashSet<Vector<N>> {
+    antennas
+        .values()
+        .flat_map(|positions| positions.iter().tuple_combinations())
+        .flat_map(|(&a, &b)| antinodes_of(a, b, rule, size))
+        .collect()
 }
 
-pub fn solve2(input: &str) -> usize {
-    let (grid, antennas) = parse(input);
+/// Two antennas of the same frequency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pair<const N: usize> {
+    pub frequency: char,
+    pub antennas: [Vector<N>; 2],
+}
 
-    let mut antinodes = HashSet::new();
-    for positions in antennas.values() {
-        for (x1, y1) in positions {
-            for (x2, y2) in positions {
-                if (x1, y1) != (x2, y2) {
-                    let (dx, dy) = (x1 - x2, y1 - y2);
-                    antinodes.extend(
-                        (0..)
-                            .map(|count| (x1 + dx * count, y1 + dy * count))
-                            .take_while(|antinode| grid.contains_key(antinode)),
-                    );
-                }
+/// Every antinode within `size`, with the pairs of antennas that make it.
+pub fn report<const N: usize>(
+    antennas: &Antennas<N>,
+    rule: Rule,
+    size: &Vector<N>,
+) -> BTreeMap<Vector<N>, Vec<Pair<N>>> {
+    let mut report = BTreeMap::<_, Vec<_>>::new();
+    for (&frequency, positions) in antennas {
+        for (&a, &b) in positions.iter().tuple_combinations() {
+            for antinode in antinodes_of(a, b, rule, size) {
+                let pair = Pair {
+                    frequency,
+                    antennas: [a, b],
+                };
+                report.entry(antinode).or_default().push(pair);
             }
         }
     }
 
-    antinodes.len()
+    for pairs in report.values_mut() {
+        pairs.sort_unstable();
+    }
+    report
+}
+
+/// One frequency's antennas and distinct antinodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub antennas: usize,
+    pub in_bounds: usize,
+    /// Antinodes outside `size`. Always 0 for `Rule::Collinear`, whose lines stop at the edge.
+    pub lost: usize,
+}
+
+/// A summary for each frequency. Antinodes shared by two frequencies count for both.
+pub fn summary<const N: usize>(
+    antennas: &Antennas<N>,
+    rule: Rule,
+    size: &Vector<N>,
+) -> BTreeMap<char, Summary> {
+    antennas
+        .iter()
+        .map(|(&frequency, positions)| {
+            let (in_bounds, lost): (HashSet<_>, HashSet<_>) = positions
+                .iter()
+                .tuple_combinations()
+                .flat_map(|(&a, &b)| candidates(a, b, rule, size))
+                .partition(|pos| contains(size, pos));
+            let summary = Summary {
+                antennas: positions.len(),
+                in_bounds: in_bounds.len(),
+                lost: lost.len(),
+            };
+
+            (frequency, summary)
+        })
+        .collect()
+}
+
+/// The map with `#` on every empty cell that holds an antinode.
+pub fn render<'a>(
+    grid: &Grid<char>,
+    antinodes: impl IntoIterator<Item = &'a Vector<2>>,
+) -> Grid<char> {
+    let mut rendered = grid.clone();
+    for &[x, y] in antinodes {
+        if let Some(cell @ '.') = rendered.get_mut(Point::new(x as Scalar, y as Scalar)) {
+            *cell = '#';
+        }
+    }
+
+    rendered
+}
+
+const fn size(grid: &Grid<char>) -> Vector<2> {
+    [grid.width() as Coordinate, grid.height() as Coordinate]
 }
 
-fn parse(input: &str) -> (Grid, Antennas) {
-    let mut grid = Grid::new();
+pub fn solve((grid, antennas): &Input, rule: Rule) -> usize {
+    antinodes(antennas, rule, &size(grid)).len()
+}
+
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let grid = Grid::parse(input)?;
     let mut antennas = Antennas::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = (x as Scalar, y as Scalar);
-            if c != '.' {
-                antennas.entry(c).or_default().push(pos);
-            }
-            grid.insert(pos, c);
+    for (pos, &c) in grid.iter() {
+        if c != '.' {
+            antennas
+                .entry(c)
+                .or_default()
+                .push([pos.x as Coordinate, pos.y as Coordinate]);
         }
     }
 
-    (grid, antennas)
+    Ok((grid, antennas))
+}
+
+pub struct Params {
+    pub part1: Rule,
+    pub part2: Rule,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1: Rule::Ratio(2),
+            part2: Rule::Collinear,
+        }
+    }
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Params = Params;
+    type Input<'a> = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, params: &Params) -> Answer {
+        solve(input, params.part1).into()
+    }
+
+    fn part2(input: &Input, params: &Params) -> Answer {
+        solve(input, params.part2).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(14, solve(include_str!("example.txt")));
-    assert_eq!(256, solve(include_str!("input.txt")));
+    assert_eq!(
+        14,
+        solve(
+            &parse(include_str!("../../inputs/day08/example.txt")).unwrap(),
+            Rule::Ratio(2)
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(8);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), Rule::Ratio(2)))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(34, solve2(include_str!("example.txt")));
-    assert_eq!(1005, solve2(include_str!("input.txt")));
+    assert_eq!(
+        34,
+        solve(
+            &parse(include_str!("../../inputs/day08/example.txt")).unwrap(),
+            Rule::Collinear
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(8);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), Rule::Collinear))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("..a.\n.a..\n...\n").unwrap_err();
+    assert_eq!(
+        "3:1: expected a row of 4 cells, found `...`",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_rules() {
+    // Grid points every `[1, 2, 3]` along the line.
+    let (a, b) = ([0, 0, 0], [3, 6, 9]);
+    let size = [100; 3];
+    assert_eq!(
+        vec![[1, 2, 3], [2, 4, 6]],
+        antinodes_of(a, b, Rule::Between, &size)
+    );
+    assert_eq!(vec![[6, 12, 18]], antinodes_of(a, b, Rule::Ratio(2), &size));
+    assert_eq!(vec![[4, 8, 12]], antinodes_of(a, b, Rule::Ratio(4), &size));
+    assert!(antinodes_of(a, b, Rule::Ratio(3), &size).is_empty());
+    assert_eq!(
+        vec![[0, 0, 0], [1, 2, 3], [2, 4, 6], [3, 6, 9]],
+        antinodes_of(a, b, Rule::Collinear, &[10; 3])
+    );
+
+    let trillion = 1_000_000_000_000;
+    let antennas = Antennas::from([(
+        'a',
+        vec![[trillion, trillion], [2 * trillion, 3 * trillion]],
+    )]);
+    assert_eq!(
+        HashSet::from([[3 * trillion, 5 * trillion]]),
+        antinodes(&antennas, Rule::Ratio(2), &[10 * trillion; 2])
+    );
+}
+
+#[test]
+fn test_report() {
+    let (grid, antennas) = parse(include_str!("../../inputs/day08/example.txt")).unwrap();
+    let report = report(&antennas, Rule::Ratio(2), &size(&grid));
+    let pair = |frequency, a, b| Pair {
+        frequency,
+        antennas: [a, b],
+    };
+    // Under the first `A`.
+    assert_eq!(vec![pair('0', [8, 1], [7, 3])], report[&[6, 5]]);
+    assert_eq!(
+        "\
+......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+",
+        render(&grid, report.keys()).to_string()
+    );
+
+    let summary = summary(&antennas, Rule::Ratio(2), &size(&grid));
+    let expected = |antennas, in_bounds, lost| Summary {
+        antennas,
+        in_bounds,
+        lost,
+    };
+    assert_eq!(expected(4, 10, 2), summary[&'0']);
+    assert_eq!(expected(3, 5, 1), summary[&'A']);
 }
@@ -1,95 +1,230 @@
-use std::iter::repeat_n;
+use aoc_core::{Answer, ParseError, Solution, Source};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
 type Id = usize;
-type Block = Option<Id>;
-type Disk = Vec<Block>;
 type Start = usize;
 type Length = usize;
 type Span = (Start, Length);
 type Files = Vec<Span>;
 type Gaps = Vec<Span>;
 type Checksum = usize;
+type Input = (Files, Gaps);
 
-pub fn part1(input: &str) -> Checksum {
-    let (mut disk, _, _) = parse(input);
+/// The longest span one digit can describe.
+const MAX_LENGTH: Length = 9;
 
-    for next in 0..(disk.len()) {
-        // Ensure disk ends with non-empty block.
-        while disk.last() == Some(&None) {
-            disk.truncate(disk.len() - 1);
-        }
-        // If next block is empty, bring last block forward.
-        if disk.get(next) == Some(&None) {
-            disk.swap_remove(next);
+/// The checksum of `length` blocks of file `id` from `start` on.
+const fn checksum(id: Id, start: Start, length: Length) -> Checksum {
+    id * (start * length + length * length.saturating_sub(1) / 2)
+}
+
+/// Moves blocks one at a time from the last file into the first gap.
+pub fn part1((files, gaps): &Input) -> Checksum {
+    let mut lengths: Vec<Length> = files.iter().map(|&(_, length)| length).collect();
+    // Files from `remaining` on have been moved entirely.
+    let mut remaining = files.len();
+    let mut total = 0;
+
+    for (index, &(mut start, mut length)) in gaps.iter().enumerate() {
+        // Gap `index` comes after file `index`, so only later files move into it.
+        while length > 0 && remaining > index + 1 {
+            let id = remaining - 1;
+            let moved = length.min(lengths[id]);
+            total += checksum(id, start, moved);
+            lengths[id] -= moved;
+            start += moved;
+            length -= moved;
+            if lengths[id] == 0 {
+                remaining -= 1;
+            }
         }
     }
 
-    checksum(&disk)
+    // What is left of each file stays where it started.
+    total
+        + files[..remaining]
+            .iter()
+            .zip(&lengths)
+            .enumerate()
+            .map(|(id, (&(start, _), &length))| checksum(id, start, length))
+            .sum::<Checksum>()
 }
 
-pub fn part2(input: &str) -> Checksum {
-    let (mut disk, files, mut gaps) = parse(input);
-
-    // Try to move each file, starting from the end.
-    for (file_start, file_len) in files.iter().rev() {
-        // Try to find suitable gap, searching from the start.
-        // Only consider gaps that come before the file.
-        // Find gap that is large enough for the file.
-        if let Some((gap_start, gap_len)) = gaps
-            .iter_mut()
-            .take_while(|(start, _)| start < file_start)
-            .find(|(_, len)| len >= file_len)
-        {
-            // Swap file blocks and gap blocks.
-            (0..*file_len).for_each(|i| disk.swap(file_start + i, *gap_start + i));
-
-            // Update gap.
-            *gap_len -= file_len;
-            *gap_start += file_len;
+/// Moves whole files, last first, into the first gap before them that is large enough.
+pub fn part2((files, gaps): &Input) -> Checksum {
+    // The starts of the gaps of each length, first first.
+    let mut starts: [BinaryHeap<Reverse<Start>>; MAX_LENGTH + 1] = Default::default();
+    for &(start, length) in gaps {
+        if length > 0 {
+            starts[length].push(Reverse(start));
         }
     }
 
-    checksum(&disk)
-}
+    let mut total = 0;
+    for (id, &(file_start, file_length)) in files.iter().enumerate().rev() {
+        if file_length == 0 {
+            continue;
+        }
+
+        // The first gap of any length that fits.
+        let gap = (file_length..=MAX_LENGTH)
+            .filter_map(|length| Some((starts[length].peek()?.0, length)))
+            .filter(|&(start, _)| start < file_start)
+            .min();
+
+        let start = match gap {
+            Some((start, length)) => {
+                starts[length].pop();
+                if length > file_length {
+                    starts[length - file_length].push(Reverse(start + file_length));
+                }
+                start
+            }
+            None => file_start,
+        };
+        total += checksum(id, start, file_length);
+    }
 
-fn checksum(disk: &Disk) -> Checksum {
-    disk.iter()
-        .enumerate()
-        .filter_map(|(pos, id)| id.map(|id| id * pos))
-        .sum()
+    total
 }
 
-fn parse(input: &str) -> (Disk, Files, Gaps) {
-    let mut disk = Disk::new();
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
     let mut files = Files::new();
     let mut gaps = Gaps::new();
+    let mut end = 0;
+
+    let digits = input.trim();
+    let mut lengths = digits.char_indices().map(|(i, c)| {
+        c.to_digit(10)
+            .map(|digit| digit as Length)
+            .ok_or_else(|| source.error(&digits[i..i + c.len_utf8()], "a digit"))
+    });
+
+    while let Some(file_length) = lengths.next().transpose()? {
+        files.push((end, file_length));
+        end += file_length;
+
+        let gap_length = lengths.next().transpose()?.unwrap_or(0);
+        gaps.push((end, gap_length));
+        end += gap_length;
+    }
+
+    Ok((files, gaps))
+}
 
-    let chars = input.trim().chars();
-    let mut lengths = chars.map(|c| c.to_digit(10).unwrap() as usize);
+pub struct Day09;
 
-    while let Some(file_len) = lengths.next() {
-        let file_id = files.len();
-        let file_start = disk.len();
-        files.push((file_start, file_len));
-        disk.extend(repeat_n(Some(file_id), file_len));
+impl Solution for Day09 {
+    type Params = ();
+    type Input<'a> = Input;
 
-        let gap_len = lengths.next().unwrap_or(0);
-        let gap_start = disk.len();
-        gaps.push((gap_start, gap_len));
-        disk.extend(repeat_n(None, gap_len));
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
     }
 
-    (disk, files, gaps)
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(1928, part1(include_str!("example.txt")));
-    assert_eq!(6332189866718, part1(include_str!("input.txt")));
+    assert_eq!(
+        1928,
+        part1(&parse(include_str!("../../inputs/day09/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(9);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(2858, part2(include_str!("example.txt")));
-    assert_eq!(6353648390778, part2(include_str!("input.txt")));
+    assert_eq!(
+        2858,
+        part2(&parse(include_str!("../../inputs/day09/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(9);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("2333x33\n").unwrap_err();
+    assert_eq!("1:5: expected a digit, found `x`", error.to_string());
+}
+
+#[test]
+fn test_long_disk() {
+    // Moves blocks around on a disk one at a time, for comparison.
+    fn blocks(digits: &str, whole_files: bool) -> Checksum {
+        let mut disk = Vec::new();
+        for (i, c) in digits.chars().enumerate() {
+            let block = (i % 2 == 0).then_some(i / 2);
+            disk.extend(std::iter::repeat_n(block, c.to_digit(10).unwrap() as usize));
+        }
+
+        if whole_files {
+            for id in (0..digits.len().div_ceil(2)).rev() {
+                let file_start = disk.iter().position(|&block| block == Some(id)).unwrap();
+                let length = disk[file_start..]
+                    .iter()
+                    .take_while(|&&block| block == Some(id))
+                    .count();
+                if let Some(gap) = (0..file_start)
+                    .find(|&start| disk[start..start + length].iter().all(Option::is_none))
+                {
+                    (0..length).for_each(|i| disk.swap(gap + i, file_start + i));
+                }
+            }
+        } else {
+            let (mut first, mut last) = (0, disk.len());
+            while first < last {
+                match (disk[first], disk[last - 1]) {
+                    (Some(_), _) => first += 1,
+                    (_, None) => last -= 1,
+                    (None, Some(_)) => disk.swap(first, last - 1),
+                }
+            }
+        }
+
+        disk.iter()
+            .enumerate()
+            .filter_map(|(pos, id)| Some(pos * (*id)?))
+            .sum()
+    }
+
+    // Pseudo-random digits. Gaps may be empty, files never are: An empty file would join
+    // the gaps around it on the disk, but not in `Gaps`.
+    let mut seed: u64 = 1;
+    let mut digits = |count| -> String {
+        (0..count)
+            .map(|i| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                let digit = u8::try_from(seed >> 60).unwrap() % 10;
+                char::from(b'0' + if i % 2 == 0 { digit.max(1) } else { digit })
+            })
+            .collect()
+    };
+
+    let short = digits(2001);
+    let input = parse(&short).unwrap();
+    assert_eq!(blocks(&short, false), part1(&input));
+    assert_eq!(blocks(&short, true), part2(&input));
+
+    // Millions of digits, too many to move block by block.
+    let input = parse(&digits(2_000_000)).unwrap();
+    assert!(part1(&input) > 0);
+    assert!(part2(&input) > 0);
 }
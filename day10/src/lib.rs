@@ -1,64 +1,88 @@
-use std::collections::HashMap;
+use aoc_core::{Answer, ParseError, Solution};
+use aoc_grid::{Grid, Point};
 use std::collections::HashSet;
 
-type Direction = (isize, isize);
-type Position = (isize, isize);
+type Position = Point;
 type Positions = Vec<Position>;
-type Grid = HashMap<Position, u32>;
-
-const DIRECTIONS: [Direction; 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-
-pub fn solve1(input: &str) -> usize {
-    let grid = parse(input);
 
+pub fn solve1(grid: &Grid<u32>) -> usize {
     grid.iter()
-        .filter_map(|(&pos, &height)| (height == 0).then_some(pos))
-        .map(|pos| goals(&grid, pos))
+        .filter_map(|(pos, &height)| (height == 0).then_some(pos))
+        .map(|pos| goals(grid, pos))
         .map(|positions| HashSet::<_>::from_iter(positions).len())
         .sum()
 }
 
-pub fn solve2(input: &str) -> usize {
-    let grid = parse(input);
-
+pub fn solve2(grid: &Grid<u32>) -> usize {
     grid.iter()
-        .filter_map(|(&pos, &height)| (height == 0).then_some(pos))
-        .map(|pos| goals(&grid, pos).len())
+        .filter_map(|(pos, &height)| (height == 0).then_some(pos))
+        .map(|pos| goals(grid, pos).len())
         .sum()
 }
 
-fn goals(grid: &Grid, pos: Position) -> Positions {
-    let height = grid[&pos];
+fn goals(grid: &Grid<u32>, pos: Position) -> Positions {
+    let height = grid[pos];
     if height == 9 {
         Vec::from([pos])
     } else {
-        DIRECTIONS
-            .iter()
-            .map(|dir| (pos.0 + dir.0, pos.1 + dir.1))
-            .filter(|next| grid.get(next) == Some(&(height + 1)))
+        grid.neighbours4(pos)
+            .filter(|&next| grid[next] == height + 1)
             .flat_map(|next| goals(grid, next))
             .collect()
     }
 }
 
-fn parse(input: &str) -> Grid {
-    let mut result = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            result.insert((x as isize, y as isize), c.to_digit(10).unwrap());
-        }
+pub fn parse(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse_with(input, "a digit", |c| c.to_digit(10))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Params = ();
+    type Input<'a> = Grid<u32>;
+
+    fn parse(input: &str) -> Result<Grid<u32>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(grid: &Grid<u32>, (): &()) -> Answer {
+        solve1(grid).into()
+    }
+
+    fn part2(grid: &Grid<u32>, (): &()) -> Answer {
+        solve2(grid).into()
     }
-    result
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(36, solve1(include_str!("example.txt")));
-    assert_eq!(782, solve1(include_str!("input.txt")));
+    assert_eq!(
+        36,
+        solve1(&parse(include_str!("../../inputs/day10/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(10);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(81, solve2(include_str!("example.txt")));
-    assert_eq!(1694, solve2(include_str!("input.txt")));
+    assert_eq!(
+        81,
+        solve2(&parse(include_str!("../../inputs/day10/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(10);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("0123\n1.34\n").unwrap_err();
+    assert_eq!("2:2: expected a digit, found `.`", error.to_string());
 }
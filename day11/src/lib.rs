@@ -1,11 +1,8 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use memoize::memoize;
 
-pub fn solve(input: &str, blink: usize) -> usize {
-    input
-        .split_whitespace()
-        .map(|stone| stone.parse().unwrap())
-        .map(|stone| evolve(blink, stone))
-        .sum()
+pub fn solve(stones: &[usize], blink: usize) -> usize {
+    stones.iter().map(|&stone| evolve(blink, stone)).sum()
 }
 
 #[memoize]
@@ -28,7 +25,7 @@ fn evolve(blink: usize, stone: usize) -> usize {
 const fn split(stone: usize) -> Option<(usize, usize)> {
     let digits = stone.ilog10() + 1;
 
-    if digits % 2 == 0 {
+    if digits.is_multiple_of(2) {
         let divisor = 10_usize.pow(digits / 2);
         Some((stone / divisor, stone % divisor))
     } else {
@@ -36,13 +33,79 @@ const fn split(stone: usize) -> Option<(usize, usize)> {
     }
 }
 
+pub fn parse(input: &str) -> Result<Vec<usize>, ParseError> {
+    let source = Source::new(input);
+
+    input
+        .split_whitespace()
+        .map(|stone| source.number(stone))
+        .collect()
+}
+
+pub struct Params {
+    pub part1_blinks: usize,
+    pub part2_blinks: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_blinks: 25,
+            part2_blinks: 75,
+        }
+    }
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Params = Params;
+    type Input<'a> = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Vec<usize>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(stones: &Vec<usize>, params: &Params) -> Answer {
+        solve(stones, params.part1_blinks).into()
+    }
+
+    fn part2(stones: &Vec<usize>, params: &Params) -> Answer {
+        solve(stones, params.part2_blinks).into()
+    }
+
+    fn reset() {
+        memoized_flush_evolve();
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(55312, solve(include_str!("example.txt"), 25));
-    assert_eq!(183248, solve(include_str!("input.txt"), 25));
+    assert_eq!(
+        55312,
+        solve(
+            &parse(include_str!("../../inputs/day11/example.txt")).unwrap(),
+            25
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(11);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), 25))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(218811774248729, solve(include_str!("input.txt"), 75));
+    let (input, ledger) = aoc_core::input::local(11);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), 75))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("125 17 -1\n").unwrap_err();
+    assert_eq!("1:8: expected a number, found `-1`", error.to_string());
 }
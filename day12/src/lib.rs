@@ -0,0 +1,138 @@
+use aoc_core::{Answer, ParseError, Solution};
+use aoc_grid::{Direction, Grid, Point};
+use std::collections::HashSet;
+
+type Position = Point;
+type Region = HashSet<Position>;
+type Price = usize;
+
+pub fn part1(grid: &Grid<char>) -> Price {
+    regions(grid)
+        .iter()
+        .map(|region| region.len() * perimeter(region))
+        .sum()
+}
+
+pub fn part2(grid: &Grid<char>) -> Price {
+    regions(grid)
+        .iter()
+        .map(|region| region.len() * sides(region))
+        .sum()
+}
+
+fn regions(grid: &Grid<char>) -> Vec<Region> {
+    let mut seen = Region::new();
+    let mut result = Vec::new();
+
+    for (start, &plant) in grid.iter() {
+        if seen.contains(&start) {
+            continue;
+        }
+
+        // Flood fill from this plot to all connected plots with the same plant.
+        let mut region = Region::new();
+        let mut stack = Vec::from([start]);
+        while let Some(pos) = stack.pop() {
+            if grid[pos] == plant && region.insert(pos) {
+                stack.extend(grid.neighbours4(pos));
+            }
+        }
+
+        seen.extend(&region);
+        result.push(region);
+    }
+
+    result
+}
+
+fn perimeter(region: &Region) -> usize {
+    // Every edge of a plot that does not touch the same region needs a fence.
+    region
+        .iter()
+        .flat_map(|&pos| Direction::ORTHOGONAL.map(|dir| pos + dir))
+        .filter(|neighbour| !region.contains(neighbour))
+        .count()
+}
+
+fn sides(region: &Region) -> usize {
+    // A polygon has as many sides as it has corners, so count corners instead.
+    let mut result = 0;
+    for &pos in region {
+        for dir in Direction::ORTHOGONAL {
+            let side1 = region.contains(&(pos + dir));
+            let side2 = region.contains(&(pos + dir.turn_right()));
+            let diagonal = region.contains(&(pos + dir + dir.turn_right()));
+
+            // Outer corner: Neither neighbour is in the region.
+            // Inner corner: Both neighbours are, but the plot between them is not.
+            if (!side1 && !side2) || (side1 && side2 && !diagonal) {
+                result += 1;
+            }
+        }
+    }
+    result
+}
+
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input)
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Params = ();
+    type Input<'a> = Grid<char>;
+
+    fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(grid: &Grid<char>, (): &()) -> Answer {
+        part1(grid).into()
+    }
+
+    fn part2(grid: &Grid<char>, (): &()) -> Answer {
+        part2(grid).into()
+    }
+}
+
+// No `input.txt` is bundled for this day, so only the examples are checked here.
+#[test]
+fn test_part1() {
+    assert_eq!(
+        140,
+        part1(&parse(include_str!("../../inputs/day12/example1.txt")).unwrap())
+    );
+    assert_eq!(
+        772,
+        part1(&parse(include_str!("../../inputs/day12/example2.txt")).unwrap())
+    );
+    assert_eq!(
+        1930,
+        part1(&parse(include_str!("../../inputs/day12/example3.txt")).unwrap())
+    );
+}
+
+#[test]
+fn test_part2() {
+    assert_eq!(
+        80,
+        part2(&parse(include_str!("../../inputs/day12/example1.txt")).unwrap())
+    );
+    assert_eq!(
+        436,
+        part2(&parse(include_str!("../../inputs/day12/example2.txt")).unwrap())
+    );
+    assert_eq!(
+        1206,
+        part2(&parse(include_str!("../../inputs/day12/example3.txt")).unwrap())
+    );
+    assert_eq!(
+        236,
+        part2(&parse(include_str!("../../inputs/day12/example4.txt")).unwrap())
+    );
+    assert_eq!(
+        368,
+        part2(&parse(include_str!("../../inputs/day12/example5.txt")).unwrap())
+    );
+}
@@ -1,11 +1,13 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
+
 type Vec2 = (isize, isize);
 type Machine = (Vec2, Vec2, Vec2);
 
-pub fn part1(input: &str, error: isize) -> isize {
-    input
-        .split("\n\n")
-        .map(|lines| parse(lines, error))
-        .filter_map(|((ax, ay), (bx, by), (px, py))| {
+pub fn part1(machines: &[Machine], error: isize) -> isize {
+    machines
+        .iter()
+        .filter_map(|&((ax, ay), (bx, by), (px, py))| {
+            let (px, py) = (px + error, py + error);
             let a = div(px * by - py * bx, ax * by - ay * bx)?;
             let b = div(px - a * ax, bx)?;
 
@@ -18,27 +20,96 @@ fn div(x: isize, y: isize) -> Option<isize> {
     (x % y == 0).then_some(x / y)
 }
 
-fn parse(lines: &str, error: isize) -> Machine {
-    let mut nums = lines
-        .split(|c: char| !c.is_ascii_digit())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap());
+pub fn parse(input: &str) -> Result<Vec<Machine>, ParseError> {
+    let source = Source::new(input);
+
+    input
+        .split("\n\n")
+        .map(|machine| parse_machine(source, machine))
+        .collect()
+}
+
+fn parse_machine<'a>(source: Source<'a>, text: &'a str) -> Result<Machine, ParseError> {
+    let mut lines = text.lines();
+    let mut vec2 = |prefix, separator| {
+        let line = source.next(&mut lines, text, &format!("`{prefix}`"))?;
+        let (x, y) = source.split_once(source.strip_prefix(line, prefix)?, separator)?;
+
+        Ok::<_, ParseError>((source.number(x)?, source.number(y)?))
+    };
+
+    let a = vec2("Button A: X+", ", Y+")?;
+    let b = vec2("Button B: X+", ", Y+")?;
+    let p = vec2("Prize: X=", ", Y=")?;
+    source.end(&mut lines)?;
+
+    Ok((a, b, p))
+}
+
+pub struct Params {
+    pub part2_error: isize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part2_error: 10000000000000,
+        }
+    }
+}
+
+pub struct Day13;
 
-    let a = (nums.next().unwrap(), nums.next().unwrap());
-    let b = (nums.next().unwrap(), nums.next().unwrap());
-    let p = (nums.next().unwrap(), nums.next().unwrap());
+impl Solution for Day13 {
+    type Params = Params;
+    type Input<'a> = Vec<Machine>;
 
-    (a, b, (p.0 + error, p.1 + error))
+    fn parse(input: &str) -> Result<Vec<Machine>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(machines: &Vec<Machine>, _: &Params) -> Answer {
+        part1(machines, 0).into()
+    }
+
+    fn part2(machines: &Vec<Machine>, params: &Params) -> Answer {
+        part1(machines, params.part2_error).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(480, part1(include_str!("example.txt"), 0));
-    assert_eq!(39996, part1(include_str!("input.txt"), 0));
+    assert_eq!(
+        480,
+        part1(
+            &parse(include_str!("../../inputs/day13/example.txt")).unwrap(),
+            0
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(13);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap(), 0))
+    );
 }
 
 #[test]
 fn test_part2() {
     let error = 10000000000000;
-    assert_eq!(73267584326867, part1(include_str!("input.txt"), error));
+    let (input, ledger) = aoc_core::input::local(13);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part1(&parse(&input).unwrap(), error))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("Button A: X+94, Y+34\nButton B: X+22\n").unwrap_err();
+    assert_eq!("2:15: expected `, Y+`, found nothing", error.to_string());
+    let error = parse("Button A: X+94, Y+34\nButton B: X+22, Y+67\n").unwrap_err();
+    assert_eq!(
+        "3:1: expected `Prize: X=`, found nothing",
+        error.to_string()
+    );
 }
@@ -1,3 +1,4 @@
+use aoc_core::{Answer, ParseError, Solution, Source, settings};
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
@@ -8,12 +9,10 @@ type Robot = (Position, Direction);
 type Count = u32;
 type Time = i32;
 
-pub fn part1(input: &str, width: Dimension, height: Dimension) -> Count {
+pub fn part1(robots: &[Robot], width: Dimension, height: Dimension) -> Count {
     let mut quadrant_counts: HashMap<(Ordering, Ordering), Count> = HashMap::new();
 
-    for line in input.lines() {
-        let ((px, py), (vx, vy)) = parse(line);
-
+    for ((px, py), (vx, vy)) in robots {
         let x = (px + 100 * vx).rem_euclid(width);
         let y = (py + 100 * vy).rem_euclid(height);
 
@@ -29,9 +28,7 @@ pub fn part1(input: &str, width: Dimension, height: Dimension) -> Count {
     quadrant_counts.values().product()
 }
 
-pub fn part2(input: &str, width: Dimension, height: Dimension) -> Time {
-    let robots: Vec<Robot> = input.lines().map(parse).collect();
-
+pub fn part2(robots: &[Robot], width: Dimension, height: Dimension) -> Time {
     // My original solution looked for this pattern:
     //
     //    .
@@ -59,25 +56,104 @@ pub fn part2(input: &str, width: Dimension, height: Dimension) -> Time {
         .unwrap()
 }
 
-fn parse(line: &str) -> Robot {
-    let mut numbers = line
-        .split(|c: char| !c.is_ascii_digit() && c != '-')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap());
+pub fn parse(input: &str) -> Result<Vec<Robot>, ParseError> {
+    let source = Source::new(input);
+
+    input
+        .lines()
+        .map(|line| parse_robot(source, line))
+        .collect()
+}
+
+fn parse_robot<'a>(source: Source<'a>, line: &'a str) -> Result<Robot, ParseError> {
+    let pair = |text, prefix| {
+        let (x, y) = source.split_once(source.strip_prefix(text, prefix)?, ",")?;
+
+        Ok::<_, ParseError>((source.number(x)?, source.number(y)?))
+    };
+
+    let (position, velocity) = source.split_once(line, " ")?;
+
+    Ok((pair(position, "p=")?, pair(velocity, "v=")?))
+}
+
+pub struct Params {
+    pub width: Dimension,
+    pub height: Dimension,
+}
 
-    (
-        (numbers.next().unwrap(), numbers.next().unwrap()),
-        (numbers.next().unwrap(), numbers.next().unwrap()),
-    )
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            width: 101,
+            height: 103,
+        }
+    }
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Params = Params;
+    type Input<'a> = Vec<Robot>;
+
+    fn parse(input: &str) -> Result<Vec<Robot>, ParseError> {
+        parse(input)
+    }
+
+    fn params(text: &str) -> Result<Params, ParseError> {
+        let mut params = Params::default();
+        settings(text, |source, key, value| {
+            match key {
+                "width" => params.width = source.number(value)?,
+                "height" => params.height = source.number(value)?,
+                _ => return Err(source.error(key, "`width` or `height`")),
+            }
+            Ok(())
+        })?;
+
+        Ok(params)
+    }
+
+    fn part1(robots: &Vec<Robot>, params: &Params) -> Answer {
+        part1(robots, params.width, params.height).into()
+    }
+
+    fn part2(robots: &Vec<Robot>, params: &Params) -> Answer {
+        part2(robots, params.width, params.height).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(12, part1(include_str!("example.txt"), 11, 7));
-    assert_eq!(218433348, part1(include_str!("input.txt"), 101, 103));
+    assert_eq!(
+        12,
+        part1(
+            &parse(include_str!("../../inputs/day14/example.txt")).unwrap(),
+            11,
+            7
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(14);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap(), 101, 103))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(6512, part2(include_str!("input.txt"), 101, 103));
+    let (input, ledger) = aoc_core::input::local(14);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap(), 101, 103))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("p=0,4 v=3,-3\np=6,3 w=-1,-3\n").unwrap_err();
+    assert_eq!("2:7: expected `v=`, found `w=-1,-3`", error.to_string());
+    let error = parse("p=0,4 v=3;-3\n").unwrap_err();
+    assert_eq!("1:13: expected `,`, found nothing", error.to_string());
 }
@@ -0,0 +1,150 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
+use aoc_grid::{Direction, Grid, Point};
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+type Position = Point;
+type Moves = Vec<Direction>;
+type Coordinate = isize;
+type Input = (Grid<char>, Position, Moves);
+
+pub fn part1((grid, robot, moves): &Input) -> Coordinate {
+    solve(grid.clone(), *robot, moves)
+}
+
+pub fn part2((grid, _, moves): &Input) -> Coordinate {
+    // Everything except the robot is twice as wide.
+    let wide = grid
+        .to_string()
+        .replace('#', "##")
+        .replace('O', "[]")
+        .replace('.', "..")
+        .replace('@', "@.");
+    let grid = Grid::parse(&wide).unwrap();
+    let robot = grid.find('@').unwrap();
+
+    solve(grid, robot, moves)
+}
+
+fn solve(mut grid: Grid<char>, mut robot: Position, moves: &[Direction]) -> Coordinate {
+    for &dir in moves {
+        robot = push(&mut grid, robot, dir);
+    }
+
+    grid.iter()
+        .filter(|&(_, &c)| c == 'O' || c == '[')
+        .map(|(pos, _)| 100 * pos.y + pos.x)
+        .sum()
+}
+
+fn push(grid: &mut Grid<char>, robot: Position, dir: Direction) -> Position {
+    // Breadth-first search for everything that moves along with the robot.
+    // A double-width box drags its other half along, which in turn may push
+    // more boxes, so one push can move a whole tree of boxes.
+    let mut moving = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([robot]);
+
+    while let Some(pos) = queue.pop_front() {
+        if !seen.insert(pos) {
+            continue;
+        }
+
+        let next = pos + dir;
+        match grid[next] {
+            '#' => return robot, // Blocked, nothing moves.
+            'O' => queue.push_back(next),
+            '[' => queue.extend([next, next + Direction::Right]),
+            ']' => queue.extend([next, next + Direction::Left]),
+            _ => (),
+        }
+        moving.push((pos, grid[pos]));
+    }
+
+    // Clear old positions first so that moved tiles do not overwrite each other.
+    for &(pos, _) in &moving {
+        grid[pos] = '.';
+    }
+    for (pos, c) in moving {
+        grid[pos + dir] = c;
+    }
+
+    robot + dir
+}
+
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
+    let (map, moves) = input
+        .split_once("\n\n")
+        .ok_or_else(|| source.missing(input, "a blank line"))?;
+
+    let grid = Grid::parse(map)?;
+    let robot = grid.find('@').ok_or_else(|| source.missing(map, "a `@`"))?;
+
+    let moves = moves
+        .char_indices()
+        .filter(|(_, c)| !c.is_whitespace())
+        .map(|(i, c)| match c {
+            '^' => Ok(Direction::Up),
+            'v' => Ok(Direction::Down),
+            '<' => Ok(Direction::Left),
+            '>' => Ok(Direction::Right),
+            _ => Err(source.error(&moves[i..i + c.len_utf8()], "one of `^v<>`")),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((grid, robot, moves))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Params = ();
+    type Input<'a> = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
+}
+
+// No `input.txt` is bundled for this day, so only the examples are checked here.
+#[test]
+fn test_part1() {
+    assert_eq!(
+        2028,
+        part1(&parse(include_str!("../../inputs/day15/example1.txt")).unwrap())
+    );
+    assert_eq!(
+        10092,
+        part1(&parse(include_str!("../../inputs/day15/example2.txt")).unwrap())
+    );
+}
+
+#[test]
+fn test_part2() {
+    assert_eq!(
+        9021,
+        part2(&parse(include_str!("../../inputs/day15/example2.txt")).unwrap())
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("#####\n#.@O#\n#####\n").unwrap_err();
+    assert_eq!(
+        "4:1: expected a blank line, found nothing",
+        error.to_string()
+    );
+    let error = parse("####\n#.O#\n####\n\n<<").unwrap_err();
+    assert_eq!("3:5: expected a `@`, found nothing", error.to_string());
+    let error = parse("####\n#@O#\n####\n\n<^\nx>").unwrap_err();
+    assert_eq!("6:1: expected one of `^v<>`, found `x`", error.to_string());
+}
@@ -1,37 +1,36 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
+use aoc_grid::{Direction, Grid, Point};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
-type Scalar = isize;
-type Position = (Scalar, Scalar);
-type Positions = HashSet<Position>;
-type Dir = (Scalar, Scalar);
-type State = (Position, Dir);
+type Position = Point;
+type State = (Position, Direction);
 type Cost = isize;
 type Costs = HashMap<State, Cost>;
 type Count = usize;
+type Input = (Grid<char>, Position, Position);
 
-pub fn part1(input: &str) -> Cost {
-    let (grid, start, end) = parse(input);
-    let (_costs, min_cost) = get_costs(&grid, start, end);
+pub fn part1((grid, start, end): &Input) -> Cost {
+    let (_costs, min_cost) = get_costs(grid, *start, *end);
 
     min_cost
 }
 
-fn get_costs(grid: &Positions, start: Position, end: Position) -> (Costs, Cost) {
+fn get_costs(grid: &Grid<char>, start: Position, end: Position) -> (Costs, Cost) {
     // https://en.wikipedia.org/wiki/Dijkstra's_algorithm
     let mut costs = HashMap::new();
-    let mut queue = BinaryHeap::from([(Reverse(0), (start, (1, 0)))]); // Facing right.
+    let mut queue = BinaryHeap::from([(Reverse(0), (start, Direction::Right))]);
 
-    while let Some((Reverse(cost), state @ ((x, y), (dx, dy)))) = queue.pop() {
+    while let Some((Reverse(cost), state @ (pos, dir))) = queue.pop() {
         let best_cost = costs.entry(state).or_insert(Cost::MAX);
-        if cost < *best_cost && grid.contains(&(x, y)) {
+        if cost < *best_cost && grid[pos] != '#' {
             costs.insert(state, cost);
 
             #[rustfmt::skip]
             queue.extend([
-                (Reverse(cost + 1),    ((x + dx, y + dy), (dx, dy))),   // Forward
-                (Reverse(cost + 1000), ((x, y),           (dy, dx))),   // Turn 90 deg
-                (Reverse(cost + 1000), ((x, y),           (-dy, -dx))), // Turn -90 deg
+                (Reverse(cost + 1),    (pos + dir, dir)),              // Forward
+                (Reverse(cost + 1000), (pos,       dir.turn_right())), // Turn 90 deg
+                (Reverse(cost + 1000), (pos,       dir.turn_left())),  // Turn -90 deg
             ]);
         }
     }
@@ -47,28 +46,27 @@ fn get_costs(grid: &Positions, start: Position, end: Position) -> (Costs, Cost)
     (costs, min_cost)
 }
 
-pub fn part2(input: &str) -> Count {
-    let (grid, start, end) = parse(input);
-    let (costs, min_cost) = get_costs(&grid, start, end);
+pub fn part2((grid, start, end): &Input) -> Count {
+    let (costs, min_cost) = get_costs(grid, *start, *end);
 
     // Breadth-first-search from end back to start.
     let mut queue: VecDeque<(Cost, State)> = costs
         .iter()
-        .filter(|&(&(pos, _dir), &cost)| pos == end && cost == min_cost)
+        .filter(|&(&(pos, _dir), &cost)| pos == *end && cost == min_cost)
         .map(|(&state, &cost)| (cost, state))
         .collect();
     let mut seen = HashSet::new();
 
-    while let Some((cost, (pos @ (px, py), (dx, dy)))) = queue.pop_front() {
-        if costs.get(&(pos, (dx, dy))) == Some(&cost) {
+    while let Some((cost, state @ (pos, dir))) = queue.pop_front() {
+        if costs.get(&state) == Some(&cost) {
             seen.insert(pos);
 
             // Inverse cost function.
             #[rustfmt::skip]
             queue.extend([
-                (cost - 1,    ((px - dx, py - dy), (dx, dy))),   // Backward
-                (cost - 1000, ((px, py),           (dy, dx))),   // Turn 90 deg
-                (cost - 1000, ((px, py),           (-dy, -dx))), // Turn -90 deg
+                (cost - 1,    (pos - dir, dir)),              // Backward
+                (cost - 1000, (pos,       dir.turn_right())), // Turn 90 deg
+                (cost - 1000, (pos,       dir.turn_left())),  // Turn -90 deg
             ]);
         }
     }
@@ -76,38 +74,79 @@ pub fn part2(input: &str) -> Count {
     seen.len()
 }
 
-fn parse(input: &str) -> (HashSet<Position>, Position, Position) {
-    let mut positions = HashSet::new();
-    let mut start = None;
-    let mut end = None;
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = (x as Scalar, y as Scalar);
-            if c != '#' {
-                positions.insert(pos);
-            }
-            if c == 'S' {
-                start = Some(pos);
-            }
-            if c == 'E' {
-                end = Some(pos);
-            }
-        }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
+    let grid = Grid::parse(input)?;
+    let start = grid
+        .find('S')
+        .ok_or_else(|| source.missing(input, "an `S`"))?;
+    let end = grid
+        .find('E')
+        .ok_or_else(|| source.missing(input, "an `E`"))?;
+
+    Ok((grid, start, end))
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Params = ();
+    type Input<'a> = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
     }
 
-    (positions, start.unwrap(), end.unwrap())
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(7036, part1(include_str!("example1.txt")));
-    assert_eq!(11048, part1(include_str!("example2.txt")));
-    assert_eq!(99460, part1(include_str!("input.txt")));
+    assert_eq!(
+        7036,
+        part1(&parse(include_str!("../../inputs/day16/example1.txt")).unwrap())
+    );
+    assert_eq!(
+        11048,
+        part1(&parse(include_str!("../../inputs/day16/example2.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(16);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(45, part2(include_str!("example1.txt")));
-    assert_eq!(64, part2(include_str!("example2.txt")));
-    assert_eq!(500, part2(include_str!("input.txt")));
+    assert_eq!(
+        45,
+        part2(&parse(include_str!("../../inputs/day16/example1.txt")).unwrap())
+    );
+    assert_eq!(
+        64,
+        part2(&parse(include_str!("../../inputs/day16/example2.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(16);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("####\n#SE#\n###\n").unwrap_err();
+    assert_eq!(
+        "3:1: expected a row of 4 cells, found `###`",
+        error.to_string()
+    );
+    let error = parse("####\n#S.#\n####\n").unwrap_err();
+    assert_eq!("4:1: expected an `E`, found nothing", error.to_string());
 }
@@ -1,20 +1,21 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use std::collections::VecDeque;
 
 type Registers = (usize, usize, usize);
 type Program = Vec<usize>;
+type Input = (Registers, Program);
 
-pub fn part1(input: &str) -> String {
-    let (registers, program) = parse(input);
-
-    run(registers, &program)
+pub fn part1((registers, program): &Input) -> String {
+    run(*registers, program)
         .iter()
         .map(usize::to_string)
         .collect::<Vec<_>>()
         .join(",")
 }
 
-pub fn part2(input: &str) -> usize {
-    let (mut registers, program) = parse(input);
+/// `None` if no value of register A makes the program print itself.
+pub fn part2((registers, program): &Input) -> Option<usize> {
+    let mut registers = *registers;
 
     // For my input, one output value is produced for every 3-bit group in
     // register A. Try finding register A values that produce just the last
@@ -22,18 +23,32 @@ pub fn part2(input: &str) -> usize {
     // more of the desired output.
 
     let mut queue: VecDeque<usize> = (0..8).collect();
-    loop {
-        registers.0 = queue.pop_front().unwrap();
-        let out = run(registers, &program);
+    while let Some(a) = queue.pop_front() {
+        registers.0 = a;
+        let out = run(registers, program);
 
-        if out == program {
-            break registers.0;
+        if out == *program {
+            return Some(a);
         }
 
-        if program.ends_with(&out) {
-            queue.extend((0..8).map(|bits| (registers.0 << 3) + bits));
+        // Zero would only come back as itself. Give up on a program that does not print one
+        // value per 3 bits once A has more groups than the program has values.
+        let bits = usize::BITS - a.leading_zeros();
+        if program.ends_with(&out)
+            && a > 0
+            && a.leading_zeros() >= 3
+            && bits + 3 <= 3 * program.len() as u32
+        {
+            queue.extend((0..8).map(|bits| (a << 3) + bits));
         }
     }
+
+    None
+}
+
+// The instructions whose operand is a combo operand, where 7 is reserved.
+const fn takes_combo(op: usize) -> bool {
+    matches!(op, 0 | 2 | 5 | 6 | 7)
 }
 
 #[allow(clippy::assign_op_pattern)]
@@ -45,12 +60,12 @@ fn run(registers: Registers, program: &Program) -> Vec<usize> {
     while let (Some(&op), Some(&literal)) = (program.get(pc), program.get(pc + 1)) {
         pc += 2;
 
+        // `parse` rules out 7, and anything above.
         let combo = match literal {
-            0..=3 => literal,
             4 => a,
             5 => b,
             6 => c,
-            _ => panic!("{literal}"),
+            _ => literal,
         };
 
         match op {
@@ -61,37 +76,116 @@ fn run(registers: Registers, program: &Program) -> Vec<usize> {
             4 => b = b ^ c,                              // bxc
             2 => b = combo % 8,                          // bst
             3 => pc = if a != 0 { literal } else { pc }, // jnz
-            5 => out.push(combo % 8),                    // out
-            _ => panic!("{op}"),
+            _ => out.push(combo % 8),                    // out
         }
     }
 
     out
 }
 
-fn parse(input: &str) -> (Registers, Program) {
-    let mut nums = input
-        .split(|c: char| !c.is_ascii_digit())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap());
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
+    let mut lines = input.lines();
+    let mut line = |prefix: &str| {
+        let line = source.next(&mut lines, input, &format!("`{prefix}`"))?;
+        source.strip_prefix(line, prefix)
+    };
 
     let registers = (
-        nums.next().unwrap(),
-        nums.next().unwrap(),
-        nums.next().unwrap(),
+        source.number(line("Register A: ")?)?,
+        source.number(line("Register B: ")?)?,
+        source.number(line("Register C: ")?)?,
     );
-    let program = nums.collect();
+    let blank = line("")?;
+    if !blank.is_empty() {
+        return Err(source.error(blank, "a blank line"));
+    }
+    let tokens: Vec<&str> = line("Program: ")?.split(',').collect();
+    let mut program = Program::with_capacity(tokens.len());
+    for (index, &token) in tokens.iter().enumerate() {
+        let number = source.number(token)?;
+        if number > 7 {
+            return Err(source.error(token, "a number from 0 to 7"));
+        }
+        if index % 2 == 1 && number == 7 && takes_combo(program[index - 1]) {
+            return Err(source.error(token, "a combo operand from 0 to 6"));
+        }
+        program.push(number);
+    }
+
+    Ok((registers, program))
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Params = ();
+    type Input<'a> = Input;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
+    }
 
-    (registers, program)
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).map_or_else(|| "none".into(), Answer::from)
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!("4,6,3,5,6,3,5,2,1,0", part1(include_str!("example.txt")));
-    assert_eq!("5,1,4,0,5,1,0,2,6", part1(include_str!("input.txt")));
+    assert_eq!(
+        "4,6,3,5,6,3,5,2,1,0",
+        part1(&parse(include_str!("../../inputs/day17/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(17);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(202322936867370, part2(include_str!("input.txt")));
+    let (input, ledger) = aoc_core::input::local(17);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()).unwrap())
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("Register A: 729\nRegister B: 0\n").unwrap_err();
+    assert_eq!(
+        "3:1: expected `Register C: `, found nothing",
+        error.to_string()
+    );
+    let error =
+        parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,x\n").unwrap_err();
+    assert_eq!("5:12: expected a number, found `x`", error.to_string());
+
+    let program = |program| {
+        let input =
+            format!("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: {program}\n");
+        parse(&input).map_err(|error| error.to_string())
+    };
+    assert_eq!(
+        Err("5:10: expected a number from 0 to 7, found `9`".to_string()),
+        program("9")
+    );
+    assert_eq!(
+        Err("5:12: expected a number from 0 to 7, found `8`".to_string()),
+        program("1,8")
+    );
+    assert_eq!(
+        Err("5:16: expected a combo operand from 0 to 6, found `7`".to_string()),
+        program("1,7,5,7")
+    );
+
+    // Prints 0 whatever A is, so never itself.
+    assert_eq!(None, part2(&program("5,0").unwrap()));
 }
@@ -1,13 +1,14 @@
+use aoc_core::{Answer, ParseError, Solution, Source, settings};
 use std::collections::HashSet;
 use std::collections::VecDeque;
 
-pub fn part1(input: &str, size: i8, time: usize) -> usize {
-    let positions = parse(input);
+type Position = (i8, i8);
+
+pub fn part1(positions: &[Position], size: i8, time: usize) -> usize {
     distance(&positions[0..time], size).unwrap()
 }
 
-pub fn part2(input: &str, size: i8) -> String {
-    let positions = parse(input);
+pub fn part2(positions: &[Position], size: i8) -> String {
     // Binary search for first time at which there is no more path:
     let times: Vec<usize> = (0..positions.len()).collect();
     let time = times.partition_point(|&time| distance(&positions[1..time], size).is_some());
@@ -15,8 +16,8 @@ pub fn part2(input: &str, size: i8) -> String {
     format!("{x},{y}")
 }
 
-fn distance(positions: &[(i8, i8)], size: i8) -> Option<usize> {
-    let mut seen: HashSet<(i8, i8)> = positions.iter().copied().collect();
+fn distance(positions: &[Position], size: i8) -> Option<usize> {
+    let mut seen: HashSet<Position> = positions.iter().copied().collect();
     let mut queue = VecDeque::from([((0, 0), 0)]);
 
     while let Some(((x, y), distance)) = queue.pop_front() {
@@ -34,22 +35,102 @@ fn distance(positions: &[(i8, i8)], size: i8) -> Option<usize> {
     None
 }
 
-fn parse(input: &str) -> Vec<(i8, i8)> {
+pub fn parse(input: &str) -> Result<Vec<Position>, ParseError> {
+    let source = Source::new(input);
+
     input
         .lines()
-        .map(|line| line.split_once(',').unwrap())
-        .map(|(x, y)| (x.parse().unwrap(), y.parse().unwrap()))
+        .map(|line| {
+            let (x, y) = source.split_once(line, ",")?;
+            Ok((source.number(x)?, source.number(y)?))
+        })
         .collect()
 }
 
+pub struct Params {
+    pub size: i8,
+    pub time: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            size: 70,
+            time: 1024,
+        }
+    }
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Params = Params;
+    type Input<'a> = Vec<Position>;
+
+    fn parse(input: &str) -> Result<Vec<Position>, ParseError> {
+        parse(input)
+    }
+
+    fn params(text: &str) -> Result<Params, ParseError> {
+        let mut params = Params::default();
+        settings(text, |source, key, value| {
+            match key {
+                "size" => params.size = source.number(value)?,
+                "time" => params.time = source.number(value)?,
+                _ => return Err(source.error(key, "`size` or `time`")),
+            }
+            Ok(())
+        })?;
+
+        Ok(params)
+    }
+
+    fn part1(positions: &Vec<Position>, params: &Params) -> Answer {
+        part1(positions, params.size, params.time).into()
+    }
+
+    fn part2(positions: &Vec<Position>, params: &Params) -> Answer {
+        part2(positions, params.size).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(22, part1(include_str!("example.txt"), 6, 12));
-    assert_eq!(232, part1(include_str!("input.txt"), 70, 1024));
+    assert_eq!(
+        22,
+        part1(
+            &parse(include_str!("../../inputs/day18/example.txt")).unwrap(),
+            6,
+            12
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(18);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap(), 70, 1024))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!("6,1", part2(include_str!("example.txt"), 6));
-    assert_eq!("44,64", part2(include_str!("input.txt"), 70));
+    assert_eq!(
+        "6,1",
+        part2(
+            &parse(include_str!("../../inputs/day18/example.txt")).unwrap(),
+            6
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(18);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap(), 70))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("5,4\n4;2\n").unwrap_err();
+    assert_eq!("2:4: expected `,`, found nothing", error.to_string());
+    let error = parse("5,4\n4,200\n").unwrap_err();
+    assert_eq!("2:3: expected a number, found `200`", error.to_string());
 }
@@ -1,26 +1,25 @@
-pub fn part1(input: &str) -> isize {
+use aoc_core::{Answer, ParseError, Solution, Source};
+
+type Pattern<'a> = &'a [u8];
+type Input<'a> = (Vec<Pattern<'a>>, Vec<Pattern<'a>>);
+
+pub fn part1(input: &Input) -> isize {
     solve(input, isize::signum)
 }
 
-pub fn part2(input: &str) -> isize {
+pub fn part2(input: &Input) -> isize {
     solve(input, std::convert::identity)
 }
 
-fn solve(input: &str, summarize: fn(isize) -> isize) -> isize {
-    let mut lines = input.lines();
-
-    let towels = lines.next().unwrap().split(", ");
-    let towels = towels.map(str::as_bytes).collect::<Vec<_>>();
-
-    lines
-        .skip(1)
-        .map(str::as_bytes)
+fn solve((towels, targets): &Input, summarize: fn(isize) -> isize) -> isize {
+    targets
+        .iter()
         .map(|target| {
             let mut counts = vec![0; target.len() + 1];
             counts[0] = 1;
 
             for prefix_len in 0..target.len() {
-                for towel in &towels {
+                for towel in towels {
                     if target[prefix_len..].starts_with(towel) {
                         counts[prefix_len + towel.len()] += counts[prefix_len];
                     }
@@ -34,14 +33,69 @@ fn solve(input: &str, summarize: fn(isize) -> isize) -> isize {
         .sum()
 }
 
+pub fn parse(input: &str) -> Result<Input<'_>, ParseError> {
+    let source = Source::new(input);
+    let mut lines = input.lines();
+
+    let towels = source
+        .next(&mut lines, input, "a list of towels")?
+        .split(", ");
+    let towels = towels.map(str::as_bytes).collect();
+    let targets = lines.skip(1).map(str::as_bytes).collect();
+
+    Ok((towels, targets))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Params = ();
+    type Input<'a> = Input<'a>;
+
+    fn parse(input: &str) -> Result<Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(6, part1(include_str!("example.txt")));
-    assert_eq!(296, part1(include_str!("input.txt")));
+    assert_eq!(
+        6,
+        part1(&parse(include_str!("../../inputs/day19/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(19);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(16, part2(include_str!("example.txt")));
-    assert_eq!(619970556776002, part2(include_str!("input.txt")));
+    assert_eq!(
+        16,
+        part2(&parse(include_str!("../../inputs/day19/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(19);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("").unwrap_err();
+    assert_eq!(
+        "1:1: expected a list of towels, found nothing",
+        error.to_string()
+    );
 }
@@ -1,34 +1,22 @@
+use aoc_core::{Answer, ParseError, Solution, Source, settings};
+use aoc_grid::{Grid, Point};
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet, VecDeque};
-
-pub fn solve(input: &str, max_cheat: i16, min_saving: i16) -> usize {
-    // Parse input.
-    let mut track = HashSet::new();
-    let mut start = None;
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = (x as i16, y as i16);
-            if c == 'S' {
-                start = Some(pos);
-            }
-            if c != '#' {
-                track.insert(pos);
-            }
-        }
-    }
+use std::collections::{HashMap, VecDeque};
+
+pub fn solve(track: &Grid<char>, max_cheat: isize, min_saving: isize) -> usize {
+    let start = track.find('S').unwrap();
 
     // Breadth-first search: Distance from start for every point on track.
-    let mut queue = VecDeque::from([(start.unwrap(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
     let mut distances = HashMap::new();
 
-    while let Some(((x, y), distance)) = queue.pop_front() {
-        let max_distance = distances.entry((x, y)).or_insert(i16::MAX);
+    while let Some((pos, distance)) = queue.pop_front() {
+        let max_distance = distances.entry(pos).or_insert(isize::MAX);
         if distance < *max_distance {
             *max_distance = distance;
 
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let next = (x + dx, y + dy);
-                if track.contains(&next) {
+            for next in track.neighbours4(pos) {
+                if track[next] != '#' {
                     queue.push_back((next, distance + 1));
                 }
             }
@@ -39,9 +27,10 @@ pub fn solve(input: &str, max_cheat: i16, min_saving: i16) -> usize {
     let mut candidates = Vec::new();
     for dx in -max_cheat..=max_cheat {
         for dy in -max_cheat..=max_cheat {
-            let distance = dx.abs() + dy.abs();
+            let candidate = Point::new(dx, dy);
+            let distance = candidate.manhattan();
             if distance > 1 && distance <= max_cheat {
-                candidates.push((dx, dy));
+                candidates.push(candidate);
             }
         }
     }
@@ -49,17 +38,17 @@ pub fn solve(input: &str, max_cheat: i16, min_saving: i16) -> usize {
     // Try cheating from every point on track...
     distances
         .par_iter()
-        .map(|(&(x, y), &dist1)| {
+        .map(|(&pos, &dist1)| {
             let mut result = 0;
 
             // ...to every cheat candidate.
-            for &(dx, dy) in &candidates {
-                if let Some(&dist2) = distances.get(&(x + dx, y + dy)) {
-                    if dist1 < dist2 {
-                        let saving = dist2 - dist1 - (dx.abs() + dy.abs());
-                        if saving >= min_saving {
-                            result += 1;
-                        }
+            for &candidate in &candidates {
+                if let Some(&dist2) = distances.get(&(pos + candidate))
+                    && dist1 < dist2
+                {
+                    let saving = dist2 - dist1 - candidate.manhattan();
+                    if saving >= min_saving {
+                        result += 1;
                     }
                 }
             }
@@ -69,14 +58,103 @@ pub fn solve(input: &str, max_cheat: i16, min_saving: i16) -> usize {
         .sum()
 }
 
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    let track = Grid::parse(input)?;
+    if track.find('S').is_none() {
+        return Err(Source::new(input).missing(input, "an `S`"));
+    }
+
+    Ok(track)
+}
+
+pub struct Params {
+    pub part1_cheat: isize,
+    pub part2_cheat: isize,
+    pub min_saving: isize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_cheat: 2,
+            part2_cheat: 20,
+            min_saving: 100,
+        }
+    }
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Params = Params;
+    type Input<'a> = Grid<char>;
+
+    fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+        parse(input)
+    }
+
+    fn params(text: &str) -> Result<Params, ParseError> {
+        let mut params = Params::default();
+        settings(text, |source, key, value| {
+            match key {
+                "part1_cheat" => params.part1_cheat = source.number(value)?,
+                "part2_cheat" => params.part2_cheat = source.number(value)?,
+                "min_saving" => params.min_saving = source.number(value)?,
+                _ => {
+                    return Err(source.error(key, "`part1_cheat`, `part2_cheat` or `min_saving`"));
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(params)
+    }
+
+    fn part1(track: &Grid<char>, params: &Params) -> Answer {
+        solve(track, params.part1_cheat, params.min_saving).into()
+    }
+
+    fn part2(track: &Grid<char>, params: &Params) -> Answer {
+        solve(track, params.part2_cheat, params.min_saving).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(44, solve(include_str!("example.txt"), 2, 1));
-    assert_eq!(1338, solve(include_str!("input.txt"), 2, 100));
+    assert_eq!(
+        44,
+        solve(
+            &parse(include_str!("../../inputs/day20/example.txt")).unwrap(),
+            2,
+            1
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(20);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), 2, 100))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(285, solve(include_str!("example.txt"), 20, 50));
-    assert_eq!(975376, solve(include_str!("input.txt"), 20, 100));
+    assert_eq!(
+        285,
+        solve(
+            &parse(include_str!("../../inputs/day20/example.txt")).unwrap(),
+            20,
+            50
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(20);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), 20, 100))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("###\n#E#\n###\n").unwrap_err();
+    assert_eq!("4:1: expected an `S`, found nothing", error.to_string());
 }
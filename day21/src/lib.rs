@@ -1,26 +1,27 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
+use aoc_grid::{Direction, Grid, Point};
 use memoize::memoize;
 use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap};
 
-type Position = (isize, isize);
+type Position = Point;
 type Button = char;
 type Cost = usize;
+type Code<'a> = (&'a str, usize);
 
 const NUM_PAD: &str = "789\n456\n123\n 0A";
 const DIR_PAD: &str = " ^A\n<v>\n";
 
-pub fn solve(input: &str, robots: usize) -> Cost {
-    input
-        .lines()
-        .map(|line| {
-            let sequence = format!("A{line}"); // Initially point at "A" on num pad.
+pub fn solve(codes: &[Code], robots: usize) -> Cost {
+    codes
+        .iter()
+        .map(|(code, numeric)| {
+            let sequence = format!("A{code}"); // Initially point at "A" on num pad.
             let cost: usize = sequence
                 .chars()
                 .zip(sequence.chars().skip(1))
                 .map(|(from, to)| get_cost(from, to, robots, NUM_PAD))
                 .sum();
-            let numeric: usize = line.strip_suffix('A').unwrap().parse().unwrap();
-
             cost * numeric
         })
         .sum()
@@ -35,7 +36,7 @@ fn get_cost(from: Button, to: Button, robots: usize, pad: &'static str) -> Cost
     }
 
     // Figure out layout of the pad the robot is pointing at (numeric or directional).
-    let (valid_positions, from_pos, to_pos) = parse_pad(pad, from, to);
+    let (grid, from_pos, to_pos) = parse_pad(pad, from, to);
 
     // https://en.wikipedia.org/wiki/Dijkstra's_algorithm
     // Search space is:
@@ -44,19 +45,19 @@ fn get_cost(from: Button, to: Button, robots: usize, pad: &'static str) -> Cost
     let mut queue = BinaryHeap::from([(Reverse(0), (from_pos, 'A'))]);
     let mut costs = HashMap::new();
 
-    while let Some((Reverse(cost), state @ ((x, y), button))) = queue.pop() {
+    while let Some((Reverse(cost), state @ (pos, button))) = queue.pop() {
         let best_cost = costs.entry(state).or_insert(Cost::MAX);
         if cost < *best_cost {
             *best_cost = cost;
 
             for (next_button, next_pos) in [
-                ('<', (x - 1, y)),
-                ('>', (x + 1, y)),
-                ('^', (x, y - 1)),
-                ('v', (x, y + 1)),
-                ('A', (x, y)),
+                ('<', pos + Direction::Left),
+                ('>', pos + Direction::Right),
+                ('^', pos + Direction::Up),
+                ('v', pos + Direction::Down),
+                ('A', pos),
             ] {
-                if valid_positions.contains(&next_pos) {
+                if grid.get(next_pos).is_some_and(|&c| c != ' ') {
                     queue.push((
                         Reverse(cost + get_cost(button, next_button, robots - 1, DIR_PAD)),
                         (next_pos, next_button),
@@ -70,39 +71,99 @@ fn get_cost(from: Button, to: Button, robots: usize, pad: &'static str) -> Cost
     costs[&(to_pos, 'A')]
 }
 
-fn parse_pad(
-    pad: &'static str,
-    from: Button,
-    to: Button,
-) -> (HashSet<Position>, Position, Position) {
-    let mut from_pos = None;
-    let mut to_pos = None;
-    let mut grid = HashSet::new();
-    for (y, line) in pad.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = (x as isize, y as isize);
-            if c == from {
-                from_pos = Some(pos);
-            }
-            if c == to {
-                to_pos = Some(pos);
-            }
-            if c != ' ' {
-                grid.insert(pos);
+fn parse_pad(pad: &'static str, from: Button, to: Button) -> (Grid<char>, Position, Position) {
+    let grid = Grid::parse(pad).unwrap();
+    let from_pos = grid.find(from).unwrap();
+    let to_pos = grid.find(to).unwrap();
+
+    (grid, from_pos, to_pos)
+}
+
+pub fn parse(input: &str) -> Result<Vec<Code<'_>>, ParseError> {
+    let source = Source::new(input);
+
+    input
+        .lines()
+        .map(|code| {
+            let digits = code
+                .strip_suffix('A')
+                .ok_or_else(|| source.missing(code, "`A`"))?;
+            if let Some((i, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+                return Err(source.error(&digits[i..i + c.len_utf8()], "a digit"));
             }
+
+            Ok((code, source.number(digits)?))
+        })
+        .collect()
+}
+
+pub struct Params {
+    pub part1_robots: usize,
+    pub part2_robots: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_robots: 2,
+            part2_robots: 25,
         }
     }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Params = Params;
+    type Input<'a> = Vec<Code<'a>>;
+
+    fn parse(input: &str) -> Result<Vec<Code<'_>>, ParseError> {
+        parse(input)
+    }
 
-    (grid, from_pos.unwrap(), to_pos.unwrap())
+    // The params count the robots at directional pads, plus one at the numeric pad.
+    fn part1(codes: &Vec<Code>, params: &Params) -> Answer {
+        solve(codes, params.part1_robots + 1).into()
+    }
+
+    fn part2(codes: &Vec<Code>, params: &Params) -> Answer {
+        solve(codes, params.part2_robots + 1).into()
+    }
+
+    fn reset() {
+        memoized_flush_get_cost();
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(126384, solve(include_str!("example.txt"), 2 + 1));
-    assert_eq!(188384, solve(include_str!("input.txt"), 2 + 1));
+    assert_eq!(
+        126384,
+        solve(
+            &parse(include_str!("../../inputs/day21/example.txt")).unwrap(),
+            2 + 1
+        )
+    );
+    let (input, ledger) = aoc_core::input::local(21);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap(), 2 + 1))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(232389969568832, solve(include_str!("input.txt"), 25 + 1));
+    let (input, ledger) = aoc_core::input::local(21);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(solve(&parse(&input).unwrap(), 25 + 1))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("029A\n980\n").unwrap_err();
+    assert_eq!("2:4: expected `A`, found nothing", error.to_string());
+    let error = parse("029A\n9<0A\n").unwrap_err();
+    assert_eq!("2:2: expected a digit, found `<`", error.to_string());
 }
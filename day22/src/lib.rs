@@ -1,21 +1,21 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use itertools::Itertools;
 use itertools::iterate;
 use std::collections::{HashMap, HashSet};
 
 type Sequence = (i8, i8, i8, i8);
 
-pub fn part1(input: &str) -> isize {
-    input
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .map(|start| iterate(start, next).nth(2000).unwrap())
+pub fn part1(starts: &[isize]) -> isize {
+    starts
+        .iter()
+        .map(|&start| iterate(start, next).nth(2000).unwrap())
         .sum()
 }
 
-pub fn part2(input: &str) -> isize {
+pub fn part2(starts: &[isize]) -> isize {
     let mut totals = HashMap::new();
 
-    for start in input.lines().map(|line| line.parse().unwrap()) {
+    for &start in starts {
         let prices = iterate(start, next).map(|x| x % 10);
         let diffs = prices.clone().tuple_windows().map(|(a, b)| (b - a) as i8);
 
@@ -39,14 +39,56 @@ const fn next(secret: &isize) -> isize {
     secret
 }
 
+pub fn parse(input: &str) -> Result<Vec<isize>, ParseError> {
+    let source = Source::new(input);
+
+    input.lines().map(|line| source.number(line)).collect()
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Params = ();
+    type Input<'a> = Vec<isize>;
+
+    fn parse(input: &str) -> Result<Vec<isize>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(starts: &Vec<isize>, (): &()) -> Answer {
+        part1(starts).into()
+    }
+
+    fn part2(starts: &Vec<isize>, (): &()) -> Answer {
+        part2(starts).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(37327623, part1(include_str!("example.txt")));
-    assert_eq!(20071921341, part1(include_str!("input.txt")));
+    assert_eq!(
+        37327623,
+        part1(&parse(include_str!("../../inputs/day22/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(22);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!(23, part2("1\n2\n3\n2024"));
-    assert_eq!(2242, part2(include_str!("input.txt")));
+    assert_eq!(23, part2(&parse("1\n2\n3\n2024").unwrap()));
+    let (input, ledger) = aoc_core::input::local(22);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("1\n10\n 100\n").unwrap_err();
+    assert_eq!("3:1: expected a number, found ` 100`", error.to_string());
 }
@@ -1,16 +1,16 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use std::collections::BTreeSet;
 use std::collections::HashMap;
 
-type Node = &'static str;
-type Nodes = BTreeSet<Node>;
-type Graph = HashMap<Node, Nodes>;
-type Clique = Vec<Node>;
+type Node<'a> = &'a str;
+type Nodes<'a> = BTreeSet<Node<'a>>;
+type Graph<'a> = HashMap<Node<'a>, Nodes<'a>>;
+type Clique<'a> = Vec<Node<'a>>;
+type Edge<'a> = (Node<'a>, Node<'a>);
 
-pub fn part1(input: &'static str) -> usize {
+pub fn part1(edges: &[Edge]) -> usize {
     let mut graph = Graph::new();
-    for line in input.lines() {
-        let (from, to) = line.split_once('-').unwrap();
-
+    for &(from, to) in edges {
         // Make all vertices go from smaller to greater node.
         if from < to {
             graph.entry(from).or_default().insert(to);
@@ -35,11 +35,10 @@ pub fn part1(input: &'static str) -> usize {
     result
 }
 
-pub fn part2(input: &'static str) -> String {
+pub fn part2(edges: &[Edge]) -> String {
     let mut graph = Graph::new();
     let mut nodes = Nodes::new();
-    for line in input.lines() {
-        let (from, to) = line.split_once('-').unwrap();
+    for &(from, to) in edges {
         graph.entry(from).or_default().insert(to);
         graph.entry(to).or_default().insert(from);
         nodes.insert(from);
@@ -74,27 +73,72 @@ pub fn part2(input: &'static str) -> String {
     result.join(",")
 }
 
-fn union(nodes: &Clique, node: Node) -> Clique {
+fn union<'a>(nodes: &Clique<'a>, node: Node<'a>) -> Clique<'a> {
     let mut result = nodes.clone();
     result.push(node);
     result
 }
 
-fn intersection(nodes1: &Nodes, nodes2: &Nodes) -> Nodes {
+fn intersection<'a>(nodes1: &Nodes<'a>, nodes2: &Nodes<'a>) -> Nodes<'a> {
     nodes1.intersection(nodes2).copied().collect()
 }
 
+pub fn parse(input: &str) -> Result<Vec<Edge<'_>>, ParseError> {
+    let source = Source::new(input);
+
+    input
+        .lines()
+        .map(|line| source.split_once(line, "-"))
+        .collect()
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Params = ();
+    type Input<'a> = Vec<Edge<'a>>;
+
+    fn parse(input: &str) -> Result<Vec<Edge<'_>>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(edges: &Vec<Edge>, (): &()) -> Answer {
+        part1(edges).into()
+    }
+
+    fn part2(edges: &Vec<Edge>, (): &()) -> Answer {
+        part2(edges).into()
+    }
+}
+
 #[test]
 fn test_part1() {
-    assert_eq!(7, part1(include_str!("example.txt")));
-    assert_eq!(1108, part1(include_str!("input.txt")));
+    assert_eq!(
+        7,
+        part1(&parse(include_str!("../../inputs/day23/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(23);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
-    assert_eq!("co,de,ka,ta", part2(include_str!("example.txt")));
     assert_eq!(
-        "ab,cp,ep,fj,fl,ij,in,ng,pl,qr,rx,va,vf",
-        part2(include_str!("input.txt"))
+        "co,de,ka,ta",
+        part2(&parse(include_str!("../../inputs/day23/example.txt")).unwrap())
     );
+    let (input, ledger) = aoc_core::input::local(23);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("kh-tc\nqp kh\n").unwrap_err();
+    assert_eq!("2:6: expected `-`, found nothing", error.to_string());
 }
@@ -1,16 +1,19 @@
+use aoc_core::{Answer, ParseError, Solution, Source};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
-type Wire = &'static str;
-type States = BTreeMap<Wire, usize>;
-type Operation = &'static str;
-type Gate = ((Wire, Wire), Operation);
-type GatesVec = Vec<(Gate, Wire)>;
-type GatesMap = HashMap<Gate, Wire>;
-type Fixes = BTreeMap<Wire, Wire>;
+type Wire<'a> = &'a str;
+type States<'a> = BTreeMap<Wire<'a>, usize>;
+type Operation<'a> = &'a str;
+type Gate<'a> = ((Wire<'a>, Wire<'a>), Operation<'a>);
+type GatesVec<'a> = Vec<(Gate<'a>, Wire<'a>)>;
+type GatesMap<'a> = HashMap<Gate<'a>, Wire<'a>>;
+type Fixes<'a> = BTreeMap<Wire<'a>, Wire<'a>>;
+type Input<'a> = (States<'a>, GatesVec<'a>);
 
-pub fn part1(input: &'static str) -> usize {
-    let (mut states, mut gates) = parse(input);
+pub fn part1((states, gates): &Input) -> usize {
+    let mut states = states.clone();
+    let mut gates = gates.clone();
 
     while !gates.is_empty() {
         gates.retain(|(((in1, in2), op), out)| {
@@ -40,9 +43,7 @@ pub fn part1(input: &'static str) -> usize {
         .unwrap()
 }
 
-pub fn part2(input: &'static str) -> String {
-    let (_states, gates) = parse(input);
-
+pub fn part2((_states, gates): &Input) -> String {
     // https://en.wikipedia.org/wiki/Adder_(electronics)
     //
     // The system tries to add two 45-bit binary numbers. It has a half adder
@@ -56,7 +57,7 @@ pub fn part2(input: &'static str) -> String {
     // will make the assertion pass, add it to the list here. Repeat until
     // no more assertions fail.
     let (fixes, gates) = apply_fixes(
-        gates,
+        gates.clone(),
         &[
             ("z16", "fkb"),
             ("nnr", "rqf"),
@@ -73,7 +74,7 @@ pub fn part2(input: &'static str) -> String {
     fixes.keys().copied().collect::<Vec<_>>().join(",")
 }
 
-fn check_half_adder(gates: &GatesMap, bit: usize) -> Wire {
+fn check_half_adder<'a>(gates: &GatesMap<'a>, bit: usize) -> Wire<'a> {
     let bit = |prefix: char| format!("{prefix}{bit:02}");
 
     let xor = gates[&(sort(&bit('x'), &bit('y')), "XOR")];
@@ -83,7 +84,7 @@ fn check_half_adder(gates: &GatesMap, bit: usize) -> Wire {
     carry
 }
 
-fn check_full_adder(gates: &GatesMap, bit: usize, carry: Wire) -> Wire {
+fn check_full_adder<'a>(gates: &GatesMap<'a>, bit: usize, carry: Wire<'a>) -> Wire<'a> {
     let bit = |prefix: char| format!("{prefix}{bit:02}");
 
     let xor1 = gates[&(sort(&bit('x'), &bit('y')), "XOR")];
@@ -105,7 +106,10 @@ fn sort<'a>(wire1: &'a str, wire2: &'a str) -> (&'a str, &'a str) {
     }
 }
 
-fn apply_fixes(gates_vec: GatesVec, pairs: &[(Wire, Wire)]) -> (Fixes, GatesMap) {
+fn apply_fixes<'a>(
+    gates_vec: GatesVec<'a>,
+    pairs: &[(Wire<'a>, Wire<'a>)],
+) -> (Fixes<'a>, GatesMap<'a>) {
     // Build map of fixes.
     let mut fixes = Fixes::new();
     for &(a, b) in pairs {
@@ -122,43 +126,91 @@ fn apply_fixes(gates_vec: GatesVec, pairs: &[(Wire, Wire)]) -> (Fixes, GatesMap)
     (fixes, gates_map)
 }
 
-fn parse(input: &'static str) -> (States, GatesVec) {
+pub fn parse(input: &str) -> Result<Input<'_>, ParseError> {
+    let source = Source::new(input);
     let mut lines = input.lines();
 
     let mut states = States::new();
-    for line in lines.by_ref() {
+    loop {
+        let line = source.next(&mut lines, input, "a blank line")?;
         if line.is_empty() {
             break;
         }
 
-        let (node, state) = line.split_once(": ").unwrap();
-        states.insert(node, state.parse().unwrap());
+        let (node, state) = source.split_once(line, ": ")?;
+        states.insert(node, source.number(state)?);
     }
 
     let mut gates = GatesVec::new();
     for line in lines {
         let mut parts = line.split_whitespace();
-        let in1 = parts.next().unwrap();
-        let op = parts.next().unwrap();
-        let in2 = parts.next().unwrap();
-        let out = parts.nth(1).unwrap();
+        let in1 = source.next(&mut parts, line, "a wire")?;
+        let op = source.next(&mut parts, line, "an operation")?;
+        if !["AND", "OR", "XOR"].contains(&op) {
+            return Err(source.error(op, "`AND`, `OR` or `XOR`"));
+        }
+        let in2 = source.next(&mut parts, line, "a wire")?;
+        let arrow = source.next(&mut parts, line, "`->`")?;
+        if arrow != "->" {
+            return Err(source.error(arrow, "`->`"));
+        }
+        let out = source.next(&mut parts, line, "a wire")?;
+        source.end(&mut parts)?;
 
         gates.push(((sort(in1, in2), op), out));
     }
 
-    (states, gates)
+    Ok((states, gates))
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Params = ();
+    type Input<'a> = Input<'a>;
+
+    fn parse(input: &str) -> Result<Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Input, (): &()) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Input, (): &()) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(2024, part1(include_str!("example.txt")));
-    assert_eq!(53325321422566, part1(include_str!("input.txt")));
+    assert_eq!(
+        2024,
+        part1(&parse(include_str!("../../inputs/day24/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(24);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(part1(&parse(&input).unwrap()))
+    );
 }
 
 #[test]
 fn test_part2() {
+    let (input, ledger) = aoc_core::input::local(24);
+    assert_eq!(
+        ledger.expected(2),
+        Answer::from(part2(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("x00: 1\ny00: 0\n\nx00 NAND y00 -> z00\n").unwrap_err();
     assert_eq!(
-        "fkb,nnr,rdn,rqf,rrn,z16,z31,z37",
-        part2(include_str!("input.txt"))
+        "4:5: expected `AND`, `OR` or `XOR`, found `NAND`",
+        error.to_string()
     );
+    let error = parse("x00: 1\ny00: 0\n\nx00 AND y00 z00\n").unwrap_err();
+    assert_eq!("4:13: expected `->`, found `z00`", error.to_string());
 }
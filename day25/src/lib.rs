@@ -1,13 +1,37 @@
-pub fn solve(input: &str) -> usize {
+use aoc_core::{Answer, ParseError, Solution, Source};
+
+type Heights = Vec<i32>;
+type Input = (Vec<Heights>, Vec<Heights>);
+
+pub fn solve((keys, locks): &Input) -> usize {
+    let mut result = 0;
+    for key in keys {
+        for lock in locks {
+            if key.iter().zip(lock.iter()).all(|(k, l)| k + l <= 5) {
+                result += 1;
+            }
+        }
+    }
+
+    result
+}
+
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let source = Source::new(input);
     let mut keys = Vec::new();
     let mut locks = Vec::new();
 
     for block in input.split("\n\n") {
         let mut counts = vec![-1; 5];
         for line in block.lines() {
-            for (x, c) in line.chars().enumerate() {
-                if c == '#' {
-                    counts[x] += 1;
+            if line.chars().count() != 5 {
+                return Err(source.error(line, "a row of 5 cells"));
+            }
+            for (x, c) in line.char_indices() {
+                match c {
+                    '#' => counts[x] += 1,
+                    '.' => (),
+                    _ => return Err(source.error(&line[x..x + c.len_utf8()], "`#` or `.`")),
                 }
             }
         }
@@ -19,20 +43,52 @@ pub fn solve(input: &str) -> usize {
         }
     }
 
-    let mut result = 0;
-    for key in &keys {
-        for lock in &locks {
-            if key.iter().zip(lock.iter()).all(|(k, l)| k + l <= 5) {
-                result += 1;
-            }
-        }
+    Ok((keys, locks))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Params = ();
+    type Input<'a> = Input;
+
+    const PARTS: u8 = 1;
+
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse(input)
     }
 
-    result
+    fn part1(input: &Input, (): &()) -> Answer {
+        solve(input).into()
+    }
+
+    fn part2(_: &Input, (): &()) -> Answer {
+        unreachable!("day 25 has no part 2")
+    }
 }
 
 #[test]
 fn test_part1() {
-    assert_eq!(3, solve(include_str!("example.txt")));
-    assert_eq!(3155, solve(include_str!("input.txt")));
+    assert_eq!(
+        3,
+        solve(&parse(include_str!("../../inputs/day25/example.txt")).unwrap())
+    );
+    let (input, ledger) = aoc_core::input::local(25);
+    assert_eq!(
+        ledger.expected(1),
+        Answer::from(solve(&parse(&input).unwrap()))
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let error = parse("#####\n.####\n.###\n").unwrap_err();
+    assert_eq!(
+        "3:1: expected a row of 5 cells, found `.###`",
+        error.to_string()
+    );
+    let error = parse("#####\n.##o#\n").unwrap_err();
+    assert_eq!("2:4: expected `#` or `.`, found `o`", error.to_string());
+    let error = parse("#####\né....\n").unwrap_err();
+    assert_eq!("2:1: expected `#` or `.`, found `é`", error.to_string());
 }